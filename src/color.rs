@@ -0,0 +1,65 @@
+use eframe::egui::{self, Color32};
+
+/// The colors a list can be given, in the order they're offered.
+pub const PALETTE: [(&str, [u8; 3]); 8] = [
+    ("Red", [220, 80, 80]),
+    ("Orange", [230, 140, 50]),
+    ("Yellow", [210, 180, 40]),
+    ("Green", [80, 170, 90]),
+    ("Teal", [40, 170, 160]),
+    ("Blue", [70, 130, 220]),
+    ("Purple", [160, 100, 210]),
+    ("Pink", [220, 100, 170]),
+];
+
+/// The color itself, for painting the strip beside a list's header.
+pub fn strip(color: [u8; 3]) -> Color32 {
+    let [r, g, b] = color;
+    Color32::from_rgb(r, g, b)
+}
+
+/// The color faded almost all the way out, for painting behind a list's items
+/// without making them any harder to read.
+pub fn wash(color: [u8; 3]) -> Color32 {
+    let [r, g, b] = color;
+    Color32::from_rgba_unmultiplied(r, g, b, 24)
+}
+
+/// A list's name tinted toward its color. It's lightened on dark backgrounds
+/// and darkened on light ones, so that it's always easy to read.
+pub fn tint(color: [u8; 3], visuals: &egui::Visuals) -> Color32 {
+    let (toward, amount) = if visuals.dark_mode {
+        (255.0, 0.4)
+    } else {
+        (0.0, 0.3)
+    };
+    let [r, g, b] = color.map(|c| (f32::from(c) + (toward - f32::from(c)) * amount) as u8);
+    Color32::from_rgb(r, g, b)
+}
+
+/// Saving colors as hex strings like "#dc5050", which are easier to read (and
+/// edit) in the data file than lists of numbers.
+pub mod hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Option<[u8; 3]>, s: S) -> Result<S::Ok, S::Error> {
+        match color {
+            Some([r, g, b]) => s.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}")),
+            None => s.serialize_none(),
+        }
+    }
+
+    /// A color that can't be read is dropped, rather than losing the whole list.
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<[u8; 3]>, D::Error> {
+        let text = Option::<String>::deserialize(d)?;
+        let digits = match text.as_deref().and_then(|text| text.strip_prefix('#')) {
+            Some(digits) if digits.len() == 6 && digits.is_ascii() => digits,
+            _ => return Ok(None),
+        };
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+        Ok(channel(0)
+            .zip(channel(2))
+            .zip(channel(4))
+            .map(|((r, g), b)| [r, g, b]))
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{Item, List};
+use eframe::egui;
+
+/// Tidy up a context typed by the user, so that "home" and "@Home " are the
+/// same thing. Returns None if there's nothing left.
+fn normalize(text: &str) -> Option<String> {
+    let text = text.trim().trim_start_matches('@').trim().to_lowercase();
+    (!text.is_empty()).then(|| format!("@{}", text.replace(' ', "-")))
+}
+
+/// Add the context of every item (and sub-item) in `items` to `contexts`.
+fn gather(items: &[Item], contexts: &mut Vec<String>) {
+    for item in items {
+        if let Some(context) = &item.context {
+            if !contexts.contains(context) {
+                contexts.push(context.clone());
+            }
+        }
+        gather(&item.children, contexts);
+    }
+}
+
+/// Every context used in `lists`, sorted.
+pub fn all(lists: &[List]) -> Vec<String> {
+    let mut contexts = Vec::new();
+    for list in lists {
+        gather(&list.items, &mut contexts);
+    }
+    contexts.sort();
+    contexts
+}
+
+/// Draw a dropdown for only showing items in one of `contexts`.
+pub fn filter_box(ui: &mut egui::Ui, filter: &mut Option<String>, contexts: &[String]) {
+    egui::ComboBox::from_id_source("context filter")
+        .selected_text(filter.as_deref().unwrap_or("any context"))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(filter, None, "any context");
+            for context in contexts {
+                ui.selectable_value(filter, Some(context.clone()), context);
+            }
+        });
+}
+
+/// Draw the choices for where `item` can be done: no context, any of the
+/// ones already in use, or a new one typed into the box at the bottom.
+/// Returns whether or not the context was changed.
+pub fn menu(ui: &mut egui::Ui, item: &mut Item, contexts: &[String]) -> bool {
+    let mut picked = None;
+    if ui.radio(item.context.is_none(), "None").clicked() {
+        picked = Some(None);
+    }
+    for context in contexts {
+        if ui
+            .radio(item.context.as_ref() == Some(context), context)
+            .clicked()
+        {
+            picked = Some(Some(context.clone()));
+        }
+    }
+    // What's been typed is kept between frames while the menu is open.
+    let id = egui::Id::new(("new context", item.id));
+    let mut text = ui.data().get_temp::<String>(id).unwrap_or_default();
+    let resp = egui::TextEdit::singleline(&mut text)
+        .hint_text("@new context")
+        .desired_width(120.0)
+        .show(ui)
+        .response;
+    if resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+        if let Some(context) = normalize(&text) {
+            picked = Some(Some(context));
+        }
+        text.clear();
+    }
+    ui.data().insert_temp(id, text);
+
+    match picked {
+        Some(context) => {
+            item.context = context;
+            ui.close_menu();
+            true
+        }
+        None => false,
+    }
+}
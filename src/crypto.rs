@@ -0,0 +1,85 @@
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use keyring::Entry;
+
+/// What an encrypted data file starts with, so that it can be told apart from
+/// a plain one.
+const MAGIC: &[u8] = b"todoish-encrypted-v1\n";
+
+/// How long the nonce after the magic is.
+const NONCE_LEN: usize = 24;
+
+/// Where the key lives in the OS keyring.
+fn entry() -> Result<Entry, String> {
+    Entry::new("todoish", "data file key")
+        .map_err(|err| format!("Couldn't reach the keyring: {err}"))
+}
+
+/// Get the key for the data file out of the keyring. If there isn't one yet
+/// and `create` is set, a new random one is made and put there.
+pub fn key(create: bool) -> Result<Key, String> {
+    let entry = entry()?;
+    let hex = match entry.get_password() {
+        Ok(hex) => hex,
+        Err(keyring::Error::NoEntry) if create => {
+            let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+            let hex: String = key.iter().map(|byte| format!("{byte:02x}")).collect();
+            entry
+                .set_password(&hex)
+                .map_err(|err| format!("Couldn't save the key to the keyring: {err}"))?;
+            return Ok(key);
+        }
+        Err(keyring::Error::NoEntry) => return Err("The key isn't in the keyring".into()),
+        Err(err) => return Err(format!("Couldn't read the key from the keyring: {err}")),
+    };
+    let bytes: Option<Vec<u8>> = (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect();
+    match bytes {
+        Some(bytes) if bytes.len() == 32 => Ok(Key::clone_from_slice(&bytes)),
+        _ => Err("The key in the keyring is broken".into()),
+    }
+}
+
+/// Whether or not `bytes` are from an encrypted data file.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Encrypt `plain` with the key from the keyring, making one first if needed.
+pub fn encrypt(plain: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(&key(true)?);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|_| "Couldn't encrypt the lists".to_string())?;
+    Ok([MAGIC, nonce.as_slice(), &sealed].concat())
+}
+
+/// Decrypt `bytes` from an encrypted data file with the key from the keyring.
+pub fn decrypt(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let rest = &bytes[MAGIC.len()..];
+    if rest.len() < NONCE_LEN {
+        return Err("The encrypted file is cut short".into());
+    }
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(&key(false)?)
+        .decrypt(XNonce::from_slice(nonce), sealed)
+        .map_err(|_| "The lists couldn't be decrypted with the key in the keyring".into())
+}
+
+/// The contents of a data file, decrypted if they need to be. Plain files are
+/// passed through as they are.
+pub fn decode(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if is_encrypted(&bytes) {
+        decrypt(&bytes)
+    } else {
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{priority::Priority, List};
+use chrono::{DateTime, Local};
+use eframe::egui;
+use std::path::Path;
+
+/// The address to open for an attachment. Anything that isn't already a URL
+/// is taken to be a file on this computer.
+fn url(attachment: &str) -> String {
+    if attachment.contains("://") || attachment.starts_with("mailto:") {
+        return attachment.into();
+    }
+    let path = Path::new(attachment);
+    match std::env::current_dir() {
+        Ok(dir) if path.is_relative() => format!("file://{}", dir.join(path).display()),
+        _ => format!("file://{}", path.display()),
+    }
+}
+
+/// Draw the details window for the item at the given (list, item) indices.
+/// Sets `changed` if the item was changed from here, and `jump` to the indices
+/// of another item if one was clicked. Returns false once the window has been
+/// closed, or if the item is gone.
+pub fn show(
+    ctx: &egui::Context,
+    lists: &mut [List],
+    list: usize,
+    item: usize,
+    changed: &mut bool,
+    jump: &mut Option<(usize, usize)>,
+) -> bool {
+    let id = match lists.get(list).and_then(|list| list.items.get(item)) {
+        Some(item) => item.id,
+        None => return false,
+    };
+
+    // Every item that links to this one.
+    let referenced_by: Vec<((usize, usize), String)> = lists
+        .iter()
+        .enumerate()
+        .flat_map(|(l, list)| {
+            list.items
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| other.links.contains(&id))
+                .map(move |(i, other)| ((l, i), format!("🔗 {} · {}", other.name, list.name)))
+        })
+        .collect();
+
+    let list = &mut lists[list];
+    let (list, item) = (&list.name, &mut list.items[item]);
+
+    let mut open = true;
+    egui::Window::new("Details")
+        .id(egui::Id::new("item details"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.heading(&item.name);
+            ui.label(egui::RichText::new(format!("in {list}")).weak());
+            ui.horizontal(|ui| {
+                ui.label(if item.is_done { "Done" } else { "Not done" });
+                match item.priority() {
+                    Some(Priority::High) => {
+                        ui.label("· Important");
+                    }
+                    Some(priority) => {
+                        ui.label(format!("· {} priority", priority.label()));
+                    }
+                    None => {}
+                }
+            });
+            // When the item was added and finished, to the minute.
+            let when = |time: DateTime<Local>| time.format("%Y-%m-%d %H:%M").to_string();
+            egui::Grid::new("item times").show(ui, |ui| {
+                ui.label(egui::RichText::new("Added").weak());
+                ui.label(when(item.created_at));
+                ui.end_row();
+                if let Some(completed) = item.completed_at {
+                    ui.label(egui::RichText::new("Completed").weak());
+                    ui.label(when(completed));
+                    ui.end_row();
+                }
+            });
+
+            if !referenced_by.is_empty() {
+                ui.separator();
+                ui.label(egui::RichText::new("Referenced by").strong());
+                // Clicking one takes you to it.
+                for (at, label) in &referenced_by {
+                    if ui.link(label).clicked() {
+                        *jump = Some(*at);
+                    }
+                }
+            }
+
+            // Files and links that go with the item, which open in whatever
+            // usually opens them.
+            ui.separator();
+            ui.label(egui::RichText::new("Attachments").strong());
+            let mut remove = None;
+            for (n, attachment) in item.attachments.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.link(attachment).on_hover_text("Open").clicked() {
+                        ui.ctx().output().open_url(url(attachment));
+                    }
+                    if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                        remove = Some(n);
+                    }
+                });
+            }
+            if let Some(n) = remove {
+                item.attachments.remove(n);
+                *changed = true;
+            }
+            ui.horizontal(|ui| {
+                // What's been typed is kept between frames.
+                let id = egui::Id::new(("new attachment", item.id));
+                let mut text = ui.data().get_temp::<String>(id).unwrap_or_default();
+                let resp =
+                    ui.add(egui::TextEdit::singleline(&mut text).hint_text("file path or URL"));
+                let enter = resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                if (ui.button("Attach").clicked() || enter) && !text.trim().is_empty() {
+                    item.attachments.push(text.trim().to_string());
+                    *changed = true;
+                    text.clear();
+                }
+                ui.data().insert_temp(id, text);
+            });
+
+            ui.separator();
+
+            ui.label(egui::RichText::new("History").strong());
+            if item.history.is_empty() {
+                ui.label(egui::RichText::new("nothing yet").weak());
+            }
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    // Newest first, since that's usually what's interesting.
+                    egui::Grid::new("item history").show(ui, |ui| {
+                        for entry in item.history.iter().rev() {
+                            ui.label(egui::RichText::new(entry.time_string()).weak());
+                            ui.label(entry.event.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+        });
+    open
+}
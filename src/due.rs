@@ -0,0 +1,201 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use eframe::egui;
+
+/// Today's date, in local time.
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Parse a date typed by the user. Accepts YYYY-MM-DD, as well as "today",
+/// "tomorrow", "next week", a day of the week (meaning the next one, never
+/// today), and "in 3 days" or "in 2 weeks".
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        "next week" => return Some(today + Duration::days(7)),
+        _ => {}
+    }
+    if let Ok(weekday) = text.parse::<Weekday>() {
+        let from = i64::from(today.weekday().num_days_from_monday());
+        let to = i64::from(weekday.num_days_from_monday());
+        return Some(today + Duration::days((to - from + 6).rem_euclid(7) + 1));
+    }
+    if let Some(rest) = text.strip_prefix("in ") {
+        let (count, unit) = rest.split_once(' ')?;
+        let count = i64::from(count.parse::<u32>().ok()?);
+        let days = match unit.trim().trim_end_matches('s') {
+            "day" => count,
+            "week" => count * 7,
+            _ => return None,
+        };
+        return today.checked_add_signed(Duration::days(days));
+    }
+    NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()
+}
+
+/// A short label for a due date, relative to today when it's close.
+pub fn label(due: NaiveDate, today: NaiveDate) -> String {
+    match (due - today).num_days() {
+        -1 => "yesterday".into(),
+        0 => "today".into(),
+        1 => "tomorrow".into(),
+        // Within the next week, the day of the week is enough.
+        2..=6 => due.format("%A").to_string(),
+        _ => due.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// What the user decided in the due date editor.
+pub enum Outcome {
+    /// Use this date.
+    Set(NaiveDate),
+    /// Leave the due date as it was.
+    Cancel,
+}
+
+/// A small popup for picking the due date of an item.
+pub struct DueEditor {
+    /// The index of the list containing the item.
+    pub list: usize,
+    /// The index of the item within its list.
+    pub item: usize,
+    /// The date as it's being typed.
+    text: String,
+    /// Where the popup is shown.
+    pos: egui::Pos2,
+    /// Whether or not the text box still needs to take focus.
+    begin_editing: bool,
+}
+
+impl DueEditor {
+    /// Start editing the due date of an item, showing the popup at `pos`.
+    pub fn new(list: usize, item: usize, due: Option<NaiveDate>, pos: egui::Pos2) -> Self {
+        Self {
+            list,
+            item,
+            text: due.map_or_else(String::new, |due| due.format("%Y-%m-%d").to_string()),
+            pos,
+            begin_editing: true,
+        }
+    }
+
+    /// Draw the popup.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Outcome> {
+        let today = today();
+        let mut outcome = None;
+        egui::Area::new("due date editor")
+            .order(egui::Order::Foreground)
+            .fixed_pos(self.pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let parsed = parse_date(&self.text, today);
+                    let resp = egui::TextEdit::singleline(&mut self.text)
+                        .hint_text("YYYY-MM-DD, friday, in 3 days")
+                        .desired_width(200.0)
+                        .show(ui)
+                        .response;
+                    if self.begin_editing {
+                        resp.request_focus();
+                        self.begin_editing = false;
+                    }
+                    if resp.lost_focus() {
+                        let (enter, escape) = {
+                            let input = ui.input();
+                            (
+                                input.key_pressed(egui::Key::Enter),
+                                input.key_pressed(egui::Key::Escape),
+                            )
+                        };
+                        if escape {
+                            outcome = Some(Outcome::Cancel);
+                        } else if let (true, Some(date)) = (enter, parsed) {
+                            outcome = Some(Outcome::Set(date));
+                        }
+                    }
+
+                    // Some shortcuts for the most common dates.
+                    ui.horizontal(|ui| {
+                        for (label, days) in [("Today", 0), ("Tomorrow", 1), ("Next week", 7)] {
+                            if ui.small_button(label).clicked() {
+                                outcome = Some(Outcome::Set(today + Duration::days(days)));
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(parsed.is_some(), egui::Button::new("Set"))
+                            .clicked()
+                        {
+                            outcome = parsed.map(Outcome::Set);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            outcome = Some(Outcome::Cancel);
+                        }
+                    });
+                });
+            });
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    /// Wednesday, January 10th, 2024.
+    fn wednesday() -> NaiveDate {
+        date(1, 10)
+    }
+
+    #[test]
+    fn words_for_nearby_days() {
+        assert_eq!(parse_date("today", wednesday()), Some(date(1, 10)));
+        assert_eq!(parse_date(" Tomorrow ", wednesday()), Some(date(1, 11)));
+        assert_eq!(parse_date("next week", wednesday()), Some(date(1, 17)));
+    }
+
+    #[test]
+    fn weekdays_are_the_next_one_and_never_today() {
+        assert_eq!(parse_date("friday", wednesday()), Some(date(1, 12)));
+        assert_eq!(parse_date("Mon", wednesday()), Some(date(1, 15)));
+        assert_eq!(parse_date("wednesday", wednesday()), Some(date(1, 17)));
+        assert_eq!(parse_date("tuesday", wednesday()), Some(date(1, 16)));
+    }
+
+    #[test]
+    fn days_and_weeks_from_now() {
+        assert_eq!(parse_date("in 1 day", wednesday()), Some(date(1, 11)));
+        assert_eq!(parse_date("in 3 days", wednesday()), Some(date(1, 13)));
+        assert_eq!(parse_date("in 2 weeks", wednesday()), Some(date(1, 24)));
+        assert_eq!(parse_date("in 0 days", wednesday()), Some(wednesday()));
+        assert_eq!(parse_date("in 3 months", wednesday()), None);
+        assert_eq!(parse_date("in a week", wednesday()), None);
+        assert_eq!(parse_date("in -1 days", wednesday()), None);
+        assert_eq!(parse_date("in 4294967295 weeks", wednesday()), None);
+    }
+
+    #[test]
+    fn dates_and_nonsense() {
+        assert_eq!(parse_date("2024-02-29", wednesday()), Some(date(2, 29)));
+        assert_eq!(parse_date("2023-02-29", wednesday()), None);
+        assert_eq!(parse_date("someday", wednesday()), None);
+        assert_eq!(parse_date("", wednesday()), None);
+    }
+
+    #[test]
+    fn labels_are_relative_when_close() {
+        assert_eq!(label(date(1, 9), wednesday()), "yesterday");
+        assert_eq!(label(date(1, 10), wednesday()), "today");
+        assert_eq!(label(date(1, 11), wednesday()), "tomorrow");
+        assert_eq!(label(date(1, 16), wednesday()), "Tuesday");
+        assert_eq!(label(date(1, 17), wednesday()), "2024-01-17");
+        assert_eq!(label(date(1, 8), wednesday()), "2024-01-08");
+    }
+}
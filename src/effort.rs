@@ -0,0 +1,27 @@
+use crate::Item;
+
+/// The estimates offered in the menu, in minutes.
+pub const PRESETS: [u32; 7] = [5, 15, 30, 60, 120, 240, 480];
+
+/// A short label for some number of minutes, like "1h 30m".
+pub fn label(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+/// How many minutes of work are left in `items`, counting the estimates of
+/// every item (and sub-item) that isn't done yet.
+pub fn remaining(items: &[Item]) -> u32 {
+    items
+        .iter()
+        .filter(|item| !item.is_done)
+        .map(|item| {
+            item.effort
+                .unwrap_or(0)
+                .saturating_add(remaining(&item.children))
+        })
+        .fold(0, u32::saturating_add)
+}
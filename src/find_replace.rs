@@ -0,0 +1,283 @@
+use crate::List;
+use eframe::egui;
+use std::{collections::HashSet, ops::Range};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+/// How text should be matched.
+pub struct FindOptions {
+    /// Whether or not upper and lower case letters are different.
+    pub case_sensitive: bool,
+    /// Whether or not matches have to be whole words.
+    pub whole_word: bool,
+}
+
+/// Whether or not `c` can be part of a word.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// If `pattern` matches `text` at its very start, return the length of the
+/// match in bytes.
+fn match_len(text: &str, pattern: &str, case_sensitive: bool) -> Option<usize> {
+    let mut len = 0;
+    let mut chars = text.chars();
+    for p in pattern.chars() {
+        let c = chars.next()?;
+        let same = if case_sensitive {
+            c == p
+        } else {
+            // Compare character by character so that letters whose lowercase
+            // form has a different byte length don't throw off the offsets.
+            c == p || c.to_lowercase().eq(p.to_lowercase())
+        };
+        if !same {
+            return None;
+        }
+        len += c.len_utf8();
+    }
+    Some(len)
+}
+
+/// Find every match of `pattern` in `text`, scanning left to right. Matches
+/// never overlap: once something matches, the search continues after it.
+pub fn find_matches(text: &str, pattern: &str, options: FindOptions) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    if pattern.is_empty() {
+        return matches;
+    }
+
+    let mut start = 0;
+    while start < text.len() {
+        let rest = &text[start..];
+        if let Some(len) = match_len(rest, pattern, options.case_sensitive) {
+            let end = start + len;
+            let bounded = !options.whole_word
+                || (!text[..start].chars().next_back().is_some_and(is_word_char)
+                    && !text[end..].chars().next().is_some_and(is_word_char));
+            if bounded {
+                matches.push(start..end);
+                start = end;
+                continue;
+            }
+        }
+        // Step forward a whole character to stay on a char boundary.
+        start += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    matches
+}
+
+/// Replace every match of `pattern` in `text` with `replacement`.
+pub fn replace_all(text: &str, pattern: &str, replacement: &str, options: FindOptions) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in find_matches(text, pattern, options) {
+        result.push_str(&text[last..range.start]);
+        result.push_str(replacement);
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+#[derive(Default)]
+/// The state of the Find & Replace dialog.
+pub struct FindReplace {
+    /// Whether or not the dialog is showing.
+    pub open: bool,
+    /// The text to search for.
+    find: String,
+    /// The text to replace it with.
+    replace: String,
+    /// How text should be matched.
+    options: FindOptions,
+    /// The (list, item) indices that the user unchecked in the preview.
+    skipped: HashSet<(usize, usize)>,
+    /// The (list, item) indices of an item the user asked to be taken to.
+    pub jump: Option<(usize, usize)>,
+}
+
+impl FindReplace {
+    /// Draw the dialog. Returns true if any items were changed.
+    pub fn show(&mut self, ctx: &egui::Context, lists: &mut [List]) -> bool {
+        let mut open = self.open;
+        let mut apply = false;
+
+        egui::Window::new("Find & Replace")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let before = (self.find.clone(), self.options);
+                egui::Grid::new("find replace fields").show(ui, |ui| {
+                    ui.label("Find");
+                    ui.text_edit_singleline(&mut self.find);
+                    ui.end_row();
+                    ui.label("Replace");
+                    ui.text_edit_singleline(&mut self.replace);
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.options.case_sensitive, "Match case");
+                    ui.checkbox(&mut self.options.whole_word, "Whole word");
+                });
+                // What's being matched has changed, so start over with the preview.
+                if before != (self.find.clone(), self.options) {
+                    self.skipped.clear();
+                }
+
+                ui.separator();
+
+                // Preview every affected item, grouped by list.
+                let mut any = false;
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for (list_idx, list) in lists.iter().enumerate() {
+                            let mut shown_header = false;
+                            for (item_idx, item) in list.items.iter().enumerate() {
+                                if find_matches(&item.name, &self.find, self.options).is_empty() {
+                                    continue;
+                                }
+                                if !shown_header {
+                                    ui.label(egui::RichText::new(&list.name).strong());
+                                    shown_header = true;
+                                }
+                                any = true;
+
+                                let key = (list_idx, item_idx);
+                                let mut included = !self.skipped.contains(&key);
+                                let preview = format!(
+                                    "{} → {}",
+                                    item.name,
+                                    replace_all(
+                                        &item.name,
+                                        &self.find,
+                                        &self.replace,
+                                        self.options
+                                    )
+                                );
+                                ui.horizontal(|ui| {
+                                    if ui.checkbox(&mut included, preview).changed() {
+                                        if included {
+                                            self.skipped.remove(&key);
+                                        } else {
+                                            self.skipped.insert(key);
+                                        }
+                                    }
+                                    // A link back to the item in its list.
+                                    if ui
+                                        .small_button(format!("↗ {}", list.name))
+                                        .on_hover_text("Show in list")
+                                        .clicked()
+                                    {
+                                        self.jump = Some(key);
+                                    }
+                                });
+                            }
+                        }
+                        if !any {
+                            ui.label(egui::RichText::new("no matches").weak());
+                        }
+                    });
+
+                ui.separator();
+                apply = ui.add_enabled(any, egui::Button::new("Apply")).clicked();
+            });
+
+        let mut changed = false;
+        if apply {
+            for (list_idx, list) in lists.iter_mut().enumerate() {
+                for (item_idx, item) in list.items.iter_mut().enumerate() {
+                    if self.skipped.contains(&(list_idx, item_idx)) {
+                        continue;
+                    }
+                    let name = replace_all(&item.name, &self.find, &self.replace, self.options);
+                    if name != item.name {
+                        item.rename(name);
+                        changed = true;
+                    }
+                }
+            }
+            open = false;
+        }
+        // Get out of the way of the item being jumped to. What's being searched
+        // for is kept, so reopening the dialog picks up where it left off.
+        if self.jump.is_some() {
+            open = false;
+        }
+
+        // Forget what was unchecked once the dialog goes away, unless it's
+        // only stepping aside for a jump.
+        if !open && self.jump.is_none() {
+            self.skipped.clear();
+        }
+        self.open = open;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANY_CASE: FindOptions = FindOptions {
+        case_sensitive: false,
+        whole_word: false,
+    };
+
+    /// Where each match starts, and what it matched.
+    fn found<'a>(text: &'a str, pattern: &str, options: FindOptions) -> Vec<(usize, &'a str)> {
+        find_matches(text, pattern, options)
+            .into_iter()
+            .map(|range| (range.start, &text[range]))
+            .collect()
+    }
+
+    #[test]
+    fn matches_never_overlap() {
+        assert_eq!(find_matches("aaaa", "aa", ANY_CASE), [0..2, 2..4]);
+        assert_eq!(found("aaa", "aa", ANY_CASE), [(0, "aa")]);
+        assert_eq!(replace_all("aaaaa", "aa", "b", ANY_CASE), "bba");
+    }
+
+    #[test]
+    fn case_only_matters_when_asked() {
+        assert_eq!(find_matches("Milk milk MILK", "milk", ANY_CASE).len(), 3);
+        let exact = FindOptions {
+            case_sensitive: true,
+            ..ANY_CASE
+        };
+        assert_eq!(found("Milk milk MILK", "milk", exact), [(5, "milk")]);
+    }
+
+    #[test]
+    fn whole_words_need_a_boundary_on_both_sides() {
+        let words = FindOptions {
+            whole_word: true,
+            ..ANY_CASE
+        };
+        let text = "cat concat cat_food cats cat.";
+        assert_eq!(find_matches(text, "cat", words), [0..3, 25..28]);
+        assert_eq!(
+            replace_all(text, "cat", "dog", words),
+            "dog concat cat_food cats dog."
+        );
+    }
+
+    #[test]
+    fn offsets_are_in_bytes_around_unicode() {
+        let text = "Café CAFÉ café";
+        assert_eq!(find_matches(text, "café", ANY_CASE), [0..5, 6..11, 12..17]);
+        assert_eq!(replace_all(text, "É", "e", ANY_CASE), "Cafe CAFe cafe");
+        assert_eq!(
+            replace_all("naïve 🍎 apple", "apple", "pear", ANY_CASE),
+            "naïve 🍎 pear"
+        );
+    }
+
+    #[test]
+    fn nothing_matches_an_empty_pattern() {
+        assert!(find_matches("anything", "", ANY_CASE).is_empty());
+        assert_eq!(replace_all("anything", "", "x", ANY_CASE), "anything");
+    }
+}
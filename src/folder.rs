@@ -0,0 +1,156 @@
+use crate::List;
+use eframe::egui::{self, collapsing_header::CollapsingState};
+
+/// Every folder used in `lists`, in the order they're drawn in.
+pub fn all(lists: &[List]) -> Vec<String> {
+    let mut folders: Vec<String> = Vec::new();
+    for folder in lists.iter().filter_map(|list| list.folder.as_ref()) {
+        if !folders.contains(folder) {
+            folders.push(folder.clone());
+        }
+    }
+    folders
+}
+
+/// Put starred lists (and folders with a starred list in them) above the rest,
+/// and keep the lists in each folder together, where the first of them is.
+/// Everything else stays in the same order. Returns whether or not anything
+/// moved.
+pub fn arrange(lists: &mut Vec<List>) -> bool {
+    let keys: Vec<(bool, usize, bool)> = lists
+        .iter()
+        .enumerate()
+        .map(|(idx, list)| {
+            let same = |other: &List| list.folder.is_some() && other.folder == list.folder;
+            let first = lists.iter().position(same).unwrap_or(idx);
+            let starred = list.starred || lists.iter().any(|other| same(other) && other.starred);
+            (!starred, first, !list.starred)
+        })
+        .collect();
+    if keys.windows(2).all(|pair| pair[0] <= pair[1]) {
+        return false;
+    }
+    // The sort is stable, so the order within each group is still up to the user.
+    let mut keyed: Vec<_> = keys.into_iter().zip(lists.drain(..)).collect();
+    keyed.sort_by_key(|&(key, _)| key);
+    lists.extend(keyed.into_iter().map(|(_, list)| list));
+    true
+}
+
+/// What each list is grouped by when they're arranged: its folder, and whether
+/// or not it's starred. A list can only be moved past the lists next to it in
+/// the same group, or `arrange` would put it straight back.
+pub fn groups(lists: &[List]) -> Vec<(Option<String>, bool)> {
+    lists
+        .iter()
+        .map(|list| (list.folder.clone(), list.starred))
+        .collect()
+}
+
+/// Draw the header of a folder, above the first list in it. Returns whether
+/// or not the folder is open.
+pub fn header(ui: &mut egui::Ui, folder: &str, force_open: bool) -> bool {
+    let id = ui.make_persistent_id(("folder", folder));
+    let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, true);
+    if force_open {
+        state.set_open(true);
+    }
+    let (_, _, body) = state
+        .show_header(ui, |ui| {
+            ui.label(egui::RichText::new(format!("📁 {folder}")).strong());
+        })
+        .body(|_| ());
+    body.is_some()
+}
+
+/// Draw the choices for which folder `list` goes in: none, any of the ones
+/// already in use, or a new one typed into the box at the bottom. Returns
+/// whether or not the folder was changed.
+pub fn menu(ui: &mut egui::Ui, list: &mut List, folders: &[String]) -> bool {
+    let mut picked = None;
+    if ui.radio(list.folder.is_none(), "None").clicked() {
+        picked = Some(None);
+    }
+    for folder in folders {
+        if ui
+            .radio(list.folder.as_ref() == Some(folder), folder)
+            .clicked()
+        {
+            picked = Some(Some(folder.clone()));
+        }
+    }
+    // What's been typed is kept between frames while the menu is open.
+    let id = egui::Id::new(("new folder", list.id));
+    let mut text = ui.data().get_temp::<String>(id).unwrap_or_default();
+    let resp = egui::TextEdit::singleline(&mut text)
+        .hint_text("new folder")
+        .desired_width(120.0)
+        .show(ui)
+        .response;
+    if resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+        let name = text.trim();
+        if !name.is_empty() {
+            picked = Some(Some(name.to_string()));
+        }
+        text.clear();
+    }
+    ui.data().insert_temp(id, text);
+
+    match picked {
+        Some(folder) => {
+            list.folder = folder;
+            ui.close_menu();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(name: &str, folder: Option<&str>, starred: bool) -> List {
+        let mut list = List::new(name.into());
+        list.folder = folder.map(String::from);
+        list.starred = starred;
+        list
+    }
+
+    fn names(lists: &[List]) -> Vec<&str> {
+        lists.iter().map(|list| list.name.as_str()).collect()
+    }
+
+    #[test]
+    fn starred_lists_and_folders_go_first() {
+        let mut lists = vec![
+            list("Chores", None, false),
+            list("Milk", Some("Shopping"), false),
+            list("Work", None, true),
+            list("Hardware", Some("Shopping"), true),
+        ];
+        assert!(arrange(&mut lists));
+        assert_eq!(names(&lists), ["Hardware", "Milk", "Work", "Chores"]);
+        assert!(!arrange(&mut lists));
+    }
+
+    #[test]
+    fn lists_in_the_same_group_stay_swapped() {
+        let lists = vec![
+            list("Work", None, true),
+            list("Home", None, true),
+            list("Milk", Some("Shopping"), false),
+            list("Hardware", Some("Shopping"), false),
+            list("Chores", None, false),
+        ];
+        let groups = groups(&lists);
+        for idx in 1..lists.len() {
+            let mut swapped = lists.clone();
+            swapped.swap(idx - 1, idx);
+            let names_before = names(&swapped).join(",");
+            arrange(&mut swapped);
+            let stayed = names(&swapped).join(",") == names_before;
+            assert_eq!(stayed, groups[idx - 1] == groups[idx], "swapping at {idx}");
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt};
+
+/// How many entries each item keeps before the oldest ones are forgotten.
+const MAX_ENTRIES: usize = 20;
+
+/// How long ago `then` was, roughly, like "5 minutes ago" or "2 days ago".
+pub fn ago(then: DateTime<Local>, now: DateTime<Local>) -> String {
+    let plural = |n: i64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+    let elapsed = now - then;
+    match elapsed.num_minutes() {
+        ..=0 => "just now".into(),
+        minutes @ 1..=59 => plural(minutes, "minute"),
+        _ => match elapsed.num_hours() {
+            hours @ ..=23 => plural(hours, "hour"),
+            _ => match elapsed.num_days() {
+                days @ ..=29 => plural(days, "day"),
+                // Past a month, the date says more than a number of days.
+                _ => format!("on {}", then.format("%Y-%m-%d")),
+            },
+        },
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "e", rename_all = "snake_case")]
+/// Something that happened to an item.
+pub enum Event {
+    /// The item was created.
+    Created,
+    /// The item was renamed.
+    Renamed { from: String, to: String },
+    /// The item was checked off.
+    Checked,
+    /// The item was unchecked.
+    Unchecked,
+    /// The item was marked as important.
+    Important,
+    /// The item was no longer marked as important.
+    Unimportant,
+    /// The item was moved from one list to another.
+    Moved { from: String, to: String },
+    /// The item's due date was set, changed, or cleared.
+    Due { date: Option<NaiveDate> },
+    /// The item was moved into its list's archive.
+    Archived,
+    /// The item was taken back out of the archive.
+    Unarchived,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Created => write!(f, "Created"),
+            Self::Renamed { from, to } => write!(f, "Renamed \"{from}\" → \"{to}\""),
+            Self::Checked => write!(f, "Checked"),
+            Self::Unchecked => write!(f, "Unchecked"),
+            Self::Important => write!(f, "Marked as important"),
+            Self::Unimportant => write!(f, "Unmarked as important"),
+            Self::Moved { from, to } => write!(f, "Moved from {from} to {to}"),
+            Self::Due { date: Some(date) } => write!(f, "Due {}", date.format("%Y-%m-%d")),
+            Self::Due { date: None } => write!(f, "Due date cleared"),
+            Self::Archived => write!(f, "Archived"),
+            Self::Unarchived => write!(f, "Restored from the archive"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+/// A single entry in an item's history.
+pub struct Entry {
+    #[serde(rename = "t")]
+    /// When it happened, in seconds since the Unix epoch.
+    pub time: i64,
+    #[serde(flatten)]
+    /// What happened.
+    pub event: Event,
+}
+
+impl Entry {
+    /// When it happened, formatted in local time.
+    pub fn time_string(&self) -> String {
+        match Local.timestamp_opt(self.time, 0).single() {
+            Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+            None => "unknown".into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(transparent)]
+/// The most recent changes to an item, oldest first.
+pub struct History(VecDeque<Entry>);
+
+impl History {
+    /// Record that `event` just happened, forgetting the oldest entry if there are too many.
+    pub fn push(&mut self, event: Event) {
+        if self.0.len() >= MAX_ENTRIES {
+            self.0.pop_front();
+        }
+        self.0.push_back(Entry {
+            time: Local::now().timestamp(),
+            event,
+        });
+    }
+
+    /// Whether or not nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The entries, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    /// How `ago` describes something that happened `seconds` before now.
+    fn ago_by(seconds: i64) -> String {
+        let now = Local.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+        ago(now - Duration::seconds(seconds), now)
+    }
+
+    #[test]
+    fn under_a_minute_is_just_now() {
+        assert_eq!(ago_by(0), "just now");
+        assert_eq!(ago_by(59), "just now");
+        // Clocks can disagree, so the future is just now too.
+        assert_eq!(ago_by(-600), "just now");
+    }
+
+    #[test]
+    fn minutes_hours_and_days_round_down() {
+        assert_eq!(ago_by(60), "1 minute ago");
+        assert_eq!(ago_by(119), "1 minute ago");
+        assert_eq!(ago_by(59 * 60 + 59), "59 minutes ago");
+        assert_eq!(ago_by(60 * 60), "1 hour ago");
+        assert_eq!(ago_by(2 * 60 * 60), "2 hours ago");
+        assert_eq!(ago_by(24 * 60 * 60 - 1), "23 hours ago");
+        assert_eq!(ago_by(24 * 60 * 60), "1 day ago");
+        assert_eq!(ago_by(30 * 24 * 60 * 60 - 1), "29 days ago");
+    }
+
+    #[test]
+    fn past_a_month_is_the_date() {
+        assert_eq!(ago_by(30 * 24 * 60 * 60), "on 2024-02-14");
+    }
+}
@@ -0,0 +1,38 @@
+use eframe::egui;
+
+/// The icons offered in the picker.
+const ICONS: [&str; 24] = [
+    "⭐", "🔥", "💡", "📌", "📅", "⏰", "📞", "✉", "💬", "🛒", "💰", "🏠", "🚗", "✈", "🍴", "💊",
+    "💻", "🔧", "📚", "🎁", "🎵", "🐶", "❤", "⚠",
+];
+
+/// How many icons go on each row of the picker.
+const COLUMNS: usize = 6;
+
+/// Draw a grid of icons to pick from, along with a button for having none.
+/// Returns whether or not `icon` was changed.
+pub fn picker(ui: &mut egui::Ui, icon: &mut Option<String>) -> bool {
+    let mut picked = None;
+    egui::Grid::new("icon picker").show(ui, |ui| {
+        for (n, choice) in ICONS.iter().enumerate() {
+            let selected = icon.as_deref() == Some(*choice);
+            if ui.selectable_label(selected, *choice).clicked() {
+                picked = Some(Some(choice.to_string()));
+            }
+            if n % COLUMNS == COLUMNS - 1 {
+                ui.end_row();
+            }
+        }
+    });
+    if icon.is_some() && ui.button("No icon").clicked() {
+        picked = Some(None);
+    }
+    match picked {
+        Some(new) => {
+            *icon = new;
+            ui.close_menu();
+            true
+        }
+        None => false,
+    }
+}
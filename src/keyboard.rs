@@ -0,0 +1,60 @@
+use eframe::egui;
+
+#[derive(Default)]
+/// The item shortcuts pressed this frame.
+pub struct Keys {
+    /// Move the focus up.
+    pub up: bool,
+    /// Move the focus down.
+    pub down: bool,
+    /// Check or uncheck the focused item.
+    pub toggle: bool,
+    /// Start renaming the focused item.
+    pub edit: bool,
+    /// Delete the focused item.
+    pub delete: bool,
+    /// Jump to the new item text box.
+    pub new_item: bool,
+    /// Stop focusing on an item.
+    pub escape: bool,
+}
+
+impl Keys {
+    /// Read the shortcuts pressed this frame. None of them count while typing
+    /// in a text box, since the keys belong to the text box then.
+    pub fn read(ctx: &egui::Context) -> Self {
+        if ctx.wants_keyboard_input() {
+            return Self::default();
+        }
+        let input = ctx.input();
+        let pressed = |key| input.key_pressed(key);
+        Self {
+            up: pressed(egui::Key::ArrowUp),
+            down: pressed(egui::Key::ArrowDown),
+            toggle: pressed(egui::Key::Space),
+            // egui doesn't know about the function keys yet, so there's no F2.
+            edit: pressed(egui::Key::Enter),
+            delete: pressed(egui::Key::Delete),
+            new_item: input.modifiers.command && pressed(egui::Key::N),
+            escape: pressed(egui::Key::Escape),
+        }
+    }
+}
+
+/// Move the focus one item up or down through the `visible` items, which are
+/// (list, item) indices in the order they were drawn. With nothing focused,
+/// this starts at the top or bottom.
+pub fn step(
+    visible: &[(usize, usize)],
+    focused: Option<(usize, usize)>,
+    down: bool,
+) -> Option<(usize, usize)> {
+    let current = focused.and_then(|focused| visible.iter().position(|&item| item == focused));
+    let next = match (current, down) {
+        (Some(idx), true) => (idx + 1).min(visible.len().saturating_sub(1)),
+        (Some(idx), false) => idx.saturating_sub(1),
+        (None, true) => 0,
+        (None, false) => visible.len().saturating_sub(1),
+    };
+    visible.get(next).copied()
+}
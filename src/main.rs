@@ -1,99 +1,1304 @@
 #![windows_subsystem = "windows"]
 
-use dirs::home_dir;
-use eframe::{egui, epaint};
+mod color;
+mod context;
+mod crypto;
+mod details;
+mod due;
+mod effort;
+mod find_replace;
+mod folder;
+mod history;
+mod icon;
+mod keyboard;
+mod markdown;
+mod markup;
+mod migrate;
+mod mini;
+mod notify;
+mod onboarding;
+mod palette;
+mod persistence;
+mod print;
+mod priority;
+mod quick_add;
+mod recurrence;
+mod rtl;
+mod scale;
+mod selection;
+mod settings;
+mod snooze;
+mod stats;
+mod swipe;
+mod sync;
+mod tag;
+mod templates;
+mod today;
+mod touch;
+mod trash;
+mod undo;
+
+use chrono::{DateTime, Local, NaiveDate};
+use due::DueEditor;
+use eframe::{
+    egui::{self, collapsing_header::CollapsingState},
+    epaint,
+};
+use find_replace::FindReplace;
+use global_hotkey::GlobalHotKeyManager;
+use history::{Event, History};
+use persistence::{data_path, load_lists, Saver};
+use priority::Priority;
+use recurrence::Recurrence;
+use selection::{Bulk, Selection};
 use serde::{Deserialize, Serialize};
-use std::{fs, thread, time};
+use settings::{LayoutDirection, Settings, Theme, TouchMode};
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time,
+};
+use tag::Tag;
+use templates::Templates;
+use undo::{Action, UndoStack};
+use uuid::Uuid;
+
+/// How long a checked off item stays in view, in seconds, when completed items
+/// are hidden.
+const LINGER: f64 = 1.0;
 
 #[derive(Serialize, Deserialize, Clone)]
 /// An indivudual item on the todo list.
 struct Item {
+    #[serde(default = "Uuid::new_v4")]
+    /// A unique id for this item, which stays the same when it's renamed.
+    id: Uuid,
     /// The name of this item.
     name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// An emoji drawn before the name, for picking the item out at a
+    /// glance, if it has one.
+    icon: Option<String>,
     /// Whether or not this item is complete.
     is_done: bool,
     /// Whether or not this item is important. (Drawn with a brighter color.)
+    /// This is the same as having a high priority.
     is_important: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    /// Whether or not this item is kept at the top of its list.
+    pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// A low or medium priority, if it has one. A high priority is kept as
+    /// `is_important` instead, so files from before priorities still read
+    /// the same (and older versions can still read newer files).
+    priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// A colored tag for grouping this item with others, if it has one.
+    tag: Option<Tag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Words for finding this item along with others like it, like "work",
+    /// which every list can be filtered down to.
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Where this item can be done, like "@home" or "@errands", if that's
+    /// been set.
+    context: Option<String>,
+    #[serde(default = "Local::now")]
+    /// When this item was created. Items from before this was kept count as
+    /// created when they were first loaded.
+    created_at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When this item was checked off, if it's done.
+    completed_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When this item was last changed, for merging with changes made to the
+    /// data file elsewhere.
+    modified_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When this item should be done by, if ever.
+    due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Roughly how many minutes this item will take, if that's been guessed.
+    effort: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// How often this item gets unchecked again, if it does.
+    recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When this item was last unchecked because it recurs.
+    last_reset: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When this item comes back, if it's been snoozed.
+    snoozed_until: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The last notification sent about this item being due, if any. It's
+    /// saved so that reopening the app doesn't send it again.
+    notified: Option<notify::Notified>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    /// Extra details about this item.
+    notes: String,
+    #[serde(default)]
+    /// Smaller steps that make up this item.
+    children: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// The ids of related items, possibly in other lists.
+    links: Vec<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Paths of files and URLs that go with this item.
+    attachments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The id of an item (possibly in another list) that has to be done
+    /// before this one can be started, if there is one.
+    blocked_by: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "History::is_empty")]
+    /// The most recent changes to this item.
+    history: History,
     #[serde(skip)]
     /// Whether or not we should begin editing this item on this frame.
     begin_editing: bool,
     #[serde(skip)]
     /// Whether or not the name of this item is currently being edited.
     editing: bool,
+    #[serde(skip)]
+    /// The name as it's being edited. This is not serialized.
+    edit_name: String,
+    #[serde(skip)]
+    /// Whether or not the notes are showing. This is not serialized.
+    notes_open: bool,
+    #[serde(skip)]
+    /// Whether or not the notes are being edited. This is not serialized.
+    editing_notes: bool,
+    #[serde(skip)]
+    /// The contents of the text box for adding a sub-item, if it's open. This
+    /// is not serialized.
+    new_child: Option<String>,
+    #[serde(skip)]
+    /// When this item was checked off in this session, so that it can stay in
+    /// view for a moment when completed items are hidden. This is not serialized.
+    checked_at: Option<f64>,
 }
 
 impl Item {
     /// Create a new item from a given name.
     fn new(name: String) -> Self {
-        Self {
+        let mut item = Self {
+            id: Uuid::new_v4(),
             name,
             is_done: false,
             is_important: false,
+            pinned: false,
+            priority: None,
+            tag: None,
+            tags: Vec::new(),
+            context: None,
+            icon: None,
+            created_at: Local::now(),
+            completed_at: None,
+            modified_at: None,
+            due: None,
+            effort: None,
+            recurrence: None,
+            last_reset: None,
+            snoozed_until: None,
+            notified: None,
+            notes: String::new(),
+            children: Vec::new(),
+            links: Vec::new(),
+            attachments: Vec::new(),
+            blocked_by: None,
+            history: History::default(),
             begin_editing: false,
             editing: false,
+            edit_name: String::new(),
+            notes_open: false,
+            editing_notes: false,
+            new_child: None,
+            checked_at: None,
+        };
+        item.history.push(Event::Created);
+        item
+    }
+
+    /// Rename this item.
+    fn rename(&mut self, name: String) {
+        if name != self.name {
+            let from = std::mem::replace(&mut self.name, name);
+            self.history.push(Event::Renamed {
+                from,
+                to: self.name.clone(),
+            });
+        }
+    }
+
+    /// Check or uncheck this item.
+    fn set_done(&mut self, done: bool) {
+        if done != self.is_done {
+            self.is_done = done;
+            self.completed_at = done.then(Local::now);
+            self.history.push(if done {
+                Event::Checked
+            } else {
+                Event::Unchecked
+            });
+        }
+    }
+
+    /// Whether or not this item counts as done. An item with sub-items is only
+    /// done once all of them are.
+    fn is_effectively_done(&self) -> bool {
+        if self.children.is_empty() {
+            self.is_done
+        } else {
+            self.children.iter().all(|child| child.is_done)
+        }
+    }
+
+    /// Check or uncheck this item along with all of its sub-items. Returns how
+    /// to undo it.
+    fn set_done_all(&mut self, done: bool) -> Action {
+        let mut actions = Vec::new();
+        for child in &mut self.children {
+            if child.is_done != done {
+                actions.push(Action::SetDone {
+                    item: child.id,
+                    done,
+                });
+                child.set_done(done);
+            }
+        }
+        if self.is_done != done {
+            actions.push(Action::SetDone {
+                item: self.id,
+                done,
+            });
+            self.set_done(done);
+        }
+        Action::Batch(actions)
+    }
+
+    /// Check or uncheck this item to match its sub-items, after one of them
+    /// changed. Returns how to undo it, if anything changed.
+    fn follow_children(&mut self) -> Option<Action> {
+        let done = self.is_effectively_done();
+        (!self.children.is_empty() && done != self.is_done).then(|| {
+            self.set_done(done);
+            Action::SetDone {
+                item: self.id,
+                done,
+            }
+        })
+    }
+
+    /// Mark or unmark this item as important.
+    fn set_important(&mut self, important: bool) {
+        if important != self.is_important {
+            self.is_important = important;
+            self.history.push(if important {
+                Event::Important
+            } else {
+                Event::Unimportant
+            });
         }
     }
+
+    /// How soon this item needs doing, if that's been set.
+    fn priority(&self) -> Option<Priority> {
+        if self.is_important {
+            Some(Priority::High)
+        } else {
+            self.priority
+        }
+    }
+
+    /// Set how soon this item needs doing.
+    fn set_priority(&mut self, priority: Option<Priority>) {
+        self.set_important(priority == Some(Priority::High));
+        self.priority = priority.filter(|&priority| priority != Priority::High);
+    }
+
+    /// Set (or clear) when this item should be done by.
+    fn set_due(&mut self, due: Option<NaiveDate>) {
+        if due != self.due {
+            self.due = due;
+            self.history.push(Event::Due { date: due });
+        }
+    }
+
+    /// Whether or not this item is hidden when completed items are, as of `now`.
+    /// Items that were just checked off stay for a moment, so the click doesn't
+    /// seem to go nowhere.
+    fn hidden(&self, now: f64) -> bool {
+        self.is_done && self.checked_at.is_none_or(|at| now - at >= LINGER)
+    }
+
+    /// Whether or not this item (or one of its children) is tagged with `tag`.
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own == tag)
+            || self.children.iter().any(|child| child.has_tag(tag))
+    }
+
+    /// Whether or not this item (or one of its children) can be done in `context`.
+    fn has_context(&self, context: &str) -> bool {
+        self.context.as_deref() == Some(context)
+            || self.children.iter().any(|child| child.has_context(context))
+    }
+
+    /// Whether or not this item (or one of its children) contains `query` in
+    /// its name or notes. `query` should already be lowercase.
+    fn matches(&self, query: &str) -> bool {
+        self.name.to_lowercase().contains(query)
+            || self.notes.to_lowercase().contains(query)
+            || self.children.iter().any(|child| child.matches(query))
+    }
+
+    /// A copy of this item (and its children) with new ids.
+    fn copy(&self) -> Self {
+        let mut item = Self::new(self.name.clone());
+        item.set_done(self.is_done);
+        item.set_important(self.is_important);
+        item.pinned = self.pinned;
+        item.priority = self.priority;
+        item.tag = self.tag;
+        item.tags = self.tags.clone();
+        item.context = self.context.clone();
+        item.icon = self.icon.clone();
+        item.completed_at = self.completed_at;
+        item.due = self.due;
+        item.effort = self.effort;
+        item.recurrence = self.recurrence;
+        item.last_reset = self.last_reset;
+        item.snoozed_until = self.snoozed_until;
+        item.notified = self.notified;
+        item.notes = self.notes.clone();
+        item.links = self.links.clone();
+        item.attachments = self.attachments.clone();
+        item.blocked_by = self.blocked_by;
+        item.children = self.children.iter().map(Item::copy).collect();
+        item
+    }
+
+    /// Record that this item was moved between lists.
+    fn moved(&mut self, from: &str, to: &str) {
+        self.history.push(Event::Moved {
+            from: from.into(),
+            to: to.into(),
+        });
+    }
+
+    /// Turn this item from the list named `from` into a list of its own named
+    /// `name`, with its children as the list's items.
+    fn into_list(self, from: &str, name: String) -> List {
+        let mut list = List::new(name);
+        list.items = self.children;
+        for item in &mut list.items {
+            item.moved(from, &list.name);
+        }
+        list
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 /// A named list of todo items.
 struct List {
+    #[serde(default = "Uuid::new_v4")]
+    /// A unique id for this list, which stays the same when it's renamed or moved.
+    id: Uuid,
     /// The name of the list.
     name: String,
     /// The items within this list.
     items: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Completed items that have been put away.
+    archived: Vec<Item>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    /// Whether or not this list is kept above the lists that aren't starred.
+    starred: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The name of the folder this list is grouped under, if it's in one.
+    folder: Option<String>,
+    #[serde(default)]
+    /// Whether or not completed items are kept below the rest.
+    sort_done_last: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "color::hex")]
+    /// The color shown beside the list's name, if it has one.
+    color: Option<[u8; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Whether or not completed items are shown in this list, or None to go
+    /// along with the setting for every list.
+    show_completed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// How often every item in this list gets unchecked, for routines like a
+    /// morning checklist, if it does.
+    reset: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    /// The hour of the day (in local time) that the list resets at.
+    reset_hour: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When the list was last reset, or when resetting was turned on.
+    last_reset: Option<DateTime<Local>>,
     #[serde(skip)]
     /// The contents of the text box used to create a new item. This is not serialized.
     new_item_name: String,
+    #[serde(skip)]
+    /// A finished item with the same name as the new item, which the user is
+    /// being asked about. This is not serialized.
+    duplicate: Option<usize>,
+    #[serde(skip)]
+    /// An item to briefly flash, along with the time the flash started (once it
+    /// has been drawn). This is not serialized.
+    highlight: Option<(usize, Option<f64>)>,
+    #[serde(skip)]
+    /// Whether or not the new item text box should take focus. This is not serialized.
+    focus_new_item: bool,
+    #[serde(skip)]
+    /// Whether or not snoozed items are showing. This is not serialized.
+    show_snoozed: bool,
 }
 
 impl List {
     /// Create a new todo list from a given name.
     fn new(name: String) -> Self {
         Self {
+            id: Uuid::new_v4(),
             name,
             items: Vec::new(),
+            archived: Vec::new(),
+            starred: false,
+            folder: None,
+            sort_done_last: false,
+            color: None,
+            show_completed: None,
+            reset: None,
+            reset_hour: 0,
+            last_reset: None,
             new_item_name: String::new(),
+            duplicate: None,
+            highlight: None,
+            focus_new_item: false,
+            show_snoozed: false,
+        }
+    }
+
+    /// How many items are done, and how many there are in total. Items with
+    /// sub-items are done once all of their sub-items are.
+    fn progress(&self) -> (usize, usize) {
+        let done = self
+            .items
+            .iter()
+            .filter(|item| item.is_effectively_done())
+            .count();
+        (done, self.items.len())
+    }
+
+    /// Move pinned items above the rest, and completed items below the rest if
+    /// the list wants that, keeping everything else in the same order. Returns
+    /// where each item ended up (by its old index), or None if nothing needed
+    /// to move.
+    fn sort_pinned_and_done(&mut self) -> Option<Vec<usize>> {
+        let done_last = self.sort_done_last;
+        let key = |item: &Item| (done_last && item.is_done, !item.pinned);
+        if self
+            .items
+            .windows(2)
+            .all(|pair| key(&pair[0]) <= key(&pair[1]))
+        {
+            return None;
+        }
+        // Both sorts are stable, so they shuffle things the same way.
+        let mut order: Vec<usize> = (0..self.items.len()).collect();
+        order.sort_by_key(|&idx| key(&self.items[idx]));
+        self.items.sort_by_key(key);
+
+        let mut moved = vec![0; order.len()];
+        for (new, old) in order.into_iter().enumerate() {
+            moved[old] = new;
+        }
+        self.duplicate = self.duplicate.map(|idx| moved[idx]);
+        if let Some((idx, _)) = &mut self.highlight {
+            *idx = moved[*idx];
+        }
+        Some(moved)
+    }
+
+    /// Put the items in the order they were checked off, with everything that
+    /// isn't done yet first.
+    fn sort_by_completion(&mut self) {
+        self.items
+            .sort_by_key(|item| (item.is_done, item.completed_at));
+        // The indices of the items have changed.
+        self.duplicate = None;
+        self.highlight = None;
+    }
+
+    /// Find the item with the given name, ignoring case and surrounding whitespace.
+    fn find_item(&self, name: &str) -> Option<usize> {
+        let name = name.trim().to_lowercase();
+        self.items
+            .iter()
+            .position(|item| item.name.trim().to_lowercase() == name)
+    }
+
+    /// Move every completed item into the archive.
+    fn archive_completed(&mut self) {
+        let (done, active): (Vec<Item>, Vec<Item>) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|item| item.is_done);
+        self.items = active;
+        for mut item in done {
+            item.history.push(Event::Archived);
+            self.archived.push(item);
+        }
+        // The indices of the remaining items have changed.
+        self.duplicate = None;
+        self.highlight = None;
+    }
+
+    /// Delete every completed item, putting them in the trash.
+    fn delete_completed(&mut self, undo: &mut UndoStack, trash: &mut Vec<trash::Entry>) {
+        let mut actions = Vec::new();
+        // Going backwards keeps the indices of the rest the same.
+        for item_idx in (0..self.items.len()).rev() {
+            if self.items[item_idx].is_done {
+                let item = self.items.remove(item_idx);
+                trash.push(trash::Entry::item(&self.name, item.clone()));
+                actions.push(Action::DeleteItem {
+                    list: self.id,
+                    parent: None,
+                    index: item_idx,
+                    item: Box::new(item),
+                });
+            }
+        }
+        // The whole thing is undone in one go.
+        if !actions.is_empty() {
+            undo.push(Action::Batch(actions));
+        }
+        self.duplicate = None;
+        self.highlight = None;
+    }
+
+    /// Add the item in the new item text box, and clear it. Returns false if
+    /// there was nothing to add.
+    fn submit_new_item(&mut self) -> bool {
+        let text = std::mem::take(&mut self.new_item_name);
+        self.duplicate = None;
+        // A leading "!" marks the item as important.
+        let (name, important) = quick_add::parse_important(&text);
+        if name.is_empty() {
+            return false;
+        }
+        let mut item = Item::new(name.into());
+        item.set_important(important);
+        self.items.push(item);
+        true
+    }
+
+    /// Turn this list into a single item in the list named `to`, with the
+    /// list's items as its children. Archived items come along at the end, so
+    /// nothing is lost. The item is done if everything in the list was done,
+    /// and important if anything in the list was important.
+    fn into_item(mut self, to: &str) -> Item {
+        self.items.append(&mut self.archived);
+        for item in &mut self.items {
+            item.moved(&self.name, to);
+        }
+        let mut item = Item::new(self.name);
+        item.set_done(!self.items.is_empty() && self.items.iter().all(|item| item.is_done));
+        item.set_important(self.items.iter().any(|item| item.is_important));
+        item.children = self.items;
+        item
+    }
+
+    /// A copy of this list and everything in it, with " (copy)" after its name.
+    fn copy(&self) -> Self {
+        let mut list = Self::new(format!("{} (copy)", self.name));
+        list.items = self.items.iter().map(Item::copy).collect();
+        list.archived = self.archived.iter().map(Item::copy).collect();
+        list.starred = self.starred;
+        list.folder = self.folder.clone();
+        list.sort_done_last = self.sort_done_last;
+        list.color = self.color;
+        list.show_completed = self.show_completed;
+        list.reset = self.reset;
+        list.reset_hour = self.reset_hour;
+        list.last_reset = self.reset.map(|_| Local::now());
+        list
+    }
+
+    /// Add an item for every non-empty line of `text`. Lines can be written as
+    /// Markdown bullets or task list items, too.
+    fn add_lines(&mut self, text: &str) {
+        self.items.extend(
+            text.lines()
+                .filter_map(markdown::parse_line)
+                .map(|(name, done)| {
+                    let mut item = Item::new(name.into());
+                    item.set_done(done);
+                    item
+                }),
+        );
+    }
+}
+
+/// The name to use when renaming something from `current` to `edited`, or
+/// None if it should stay as it was. Pressing Escape cancels the rename, and a
+/// blank name is never used.
+fn finish_rename(current: &str, edited: &str, cancelled: bool) -> Option<String> {
+    let edited = edited.trim();
+    (!cancelled && !edited.is_empty() && edited != current).then(|| edited.into())
+}
+
+/// Rename the list at `idx` to `edited`, trimmed. Nothing changes if the name
+/// is blank or the same, and a name that another list already has (ignoring
+/// case) is turned away, along with why. Returns whether or not the list was
+/// renamed.
+fn rename_list(lists: &mut [List], idx: usize, edited: &str) -> Result<bool, String> {
+    let Some(name) = finish_rename(&lists[idx].name, edited, false) else {
+        return Ok(false);
+    };
+    if find_list(lists, &name).is_some_and(|other| other != idx) {
+        return Err(format!("There's already a list named \"{name}\"."));
+    }
+    lists[idx].name = name;
+    Ok(true)
+}
+
+/// Find the list with the given name, ignoring case and surrounding whitespace.
+fn find_list(lists: &[List], name: &str) -> Option<usize> {
+    let name = name.trim().to_lowercase();
+    lists
+        .iter()
+        .position(|list| list.name.trim().to_lowercase() == name)
+}
+
+/// Whether or not `n` is zero, for leaving it out of the data file.
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// `name`, or if there's already a list with that name, `name` with the first
+/// number after it that makes it unique, like "Party 2".
+fn unique_list_name(lists: &[List], name: &str) -> String {
+    if find_list(lists, name).is_none() {
+        return name.into();
+    }
+    (2..)
+        .map(|n| format!("{name} {n}"))
+        .find(|name| find_list(lists, name).is_none())
+        .expect("Ran out of numbers")
+}
+
+/// Find the list with the given name (ignoring case), creating it if it doesn't exist.
+fn list_named<'a>(lists: &'a mut Vec<List>, name: &str) -> &'a mut List {
+    let idx = find_list(lists, name).unwrap_or_else(|| {
+        lists.push(List::new(name.into()));
+        lists.len() - 1
+    });
+    &mut lists[idx]
+}
+
+/// Read the text of a file that was dropped onto the window.
+fn dropped_text(file: &egui::DroppedFile) -> Option<String> {
+    match (&file.bytes, &file.path) {
+        (Some(bytes), _) => String::from_utf8(bytes.to_vec()).ok(),
+        (None, Some(path)) => fs::read_to_string(path).ok(),
+        (None, None) => None,
+    }
+}
+
+/// How the name of an item is drawn, after its icon if it has one, with any
+/// Markdown formatting in it. Done items are struck out and dimmed. Until
+/// they're done, important (high priority) items are underlined, medium
+/// priority ones are in italics, and low priority ones are dimmed, as are
+/// items that are `blocked`.
+fn item_text(style: &egui::Style, item: &Item, blocked: bool) -> egui::text::LayoutJob {
+    let visuals = &style.visuals;
+    let mut format = egui::TextFormat {
+        font_id: egui::TextStyle::Body.resolve(style),
+        color: visuals.text_color(),
+        ..Default::default()
+    };
+    match (item.is_done, item.priority()) {
+        (true, _) => {
+            format.color = visuals.weak_text_color();
+            format.strikethrough = egui::Stroke::new(1.0, format.color);
+        }
+        (false, Some(Priority::High)) => {
+            format.underline = egui::Stroke::new(1.0, format.color);
+        }
+        (false, Some(Priority::Medium)) => format.italics = true,
+        (false, Some(Priority::Low)) => format.color = visuals.weak_text_color(),
+        (false, None) => {}
+    }
+    if blocked {
+        format.color = visuals.weak_text_color();
+    }
+    let name = match &item.icon {
+        Some(icon) => format!("{icon} {}", item.name),
+        None => item.name.clone(),
+    };
+    markup::layout(&name, format, visuals)
+}
+
+/// How many days an item has to be open for before its age is shown.
+const SHOW_AGE_AFTER: i64 = 7;
+
+/// Draw the row for an item that isn't being renamed: its checkbox, anything
+/// it's marked with, and its due date at the far end. `blocked_by` is the name
+/// of the unfinished item this one is waiting on, if any, and `show_age` is
+/// whether or not old items show their age. Search results get a button for
+/// going to the item in `home`, its list, which sets `go_home` when clicked.
+/// Returns the checkbox.
+#[allow(clippy::too_many_arguments)]
+fn item_row(
+    ui: &mut egui::Ui,
+    item: &mut Item,
+    done: &mut bool,
+    today: NaiveDate,
+    rtl: bool,
+    blocked_by: Option<&str>,
+    show_age: bool,
+    home: Option<&str>,
+    go_home: &mut bool,
+) -> egui::Response {
+    let resp = ui
+        .horizontal(|ui| {
+            // Tagged items get a dot in the tag's color before the checkbox.
+            if let Some(tag) = item.tag {
+                let (rect, resp) =
+                    ui.allocate_exact_size(egui::vec2(8.0, 8.0), egui::Sense::hover());
+                ui.painter().circle_filled(rect.center(), 4.0, tag.color());
+                resp.on_hover_text(tag.label());
+            }
+            // Hovering an item shows when it was added and finished.
+            let partial = !*done && item.children.iter().any(|child| child.is_done);
+            // Blocked items are dimmed until whatever they're waiting on is done.
+            let text = item_text(ui.style(), item, blocked_by.is_some());
+            let resp = rtl::checkbox(ui, done, text).on_hover_ui(|ui| {
+                let now = Local::now();
+                ui.label(format!("Added {}", history::ago(item.created_at, now)));
+                if let Some(completed) = item.completed_at {
+                    ui.label(format!("Completed {}", history::ago(completed, now)));
+                }
+            });
+            // Items with only some of their sub-items done get a dash in the box.
+            if partial {
+                let (mut icon, _) = ui.spacing().icon_rectangles(resp.rect);
+                if rtl {
+                    icon = icon.translate(egui::vec2(resp.rect.right() - icon.right(), 0.0));
+                }
+                let y = icon.center().y;
+                ui.painter().line_segment(
+                    [
+                        egui::pos2(icon.left() + 3.0, y),
+                        egui::pos2(icon.right() - 3.0, y),
+                    ],
+                    ui.style().interact(&resp).fg_stroke,
+                );
+            }
+            // Pinned items are marked as such.
+            if item.pinned {
+                ui.label(egui::RichText::new("📌").weak())
+                    .on_hover_text("Pinned");
+            }
+            // Recurring items are marked as such.
+            if let Some(recurrence) = item.recurrence {
+                ui.label(egui::RichText::new("↻").weak())
+                    .on_hover_text(format!("Repeats {}", recurrence.label().to_lowercase()));
+            }
+            // Items show their tags, where they can be done, and how long they
+            // should take.
+            for tag in &item.tags {
+                ui.label(egui::RichText::new(format!("#{tag}")).weak());
+            }
+            if let Some(context) = &item.context {
+                ui.label(egui::RichText::new(context).weak());
+            }
+            if let Some(effort) = item.effort {
+                ui.label(egui::RichText::new(effort::label(effort)).weak())
+                    .on_hover_text("Effort");
+            }
+            // Blocked items say what they're waiting on.
+            if let Some(blocker) = blocked_by {
+                ui.label(egui::RichText::new(format!("blocked by {blocker}")).weak());
+            }
+            // Items that have been sitting around for a while say how long,
+            // so that stale ones stand out.
+            let age = (today - item.created_at.date_naive()).num_days();
+            if show_age && !item.is_done && age >= SHOW_AGE_AFTER {
+                ui.label(egui::RichText::new(format!("{age}d")).weak())
+                    .on_hover_text(format!("Open for {age} days"));
+            }
+            // Items with attachments get a paperclip. The attachments
+            // themselves are listed in the details.
+            if !item.attachments.is_empty() {
+                ui.label(egui::RichText::new("📎").weak())
+                    .on_hover_text(format!("{} attached", item.attachments.len()));
+            }
+            // Items with sub-items show how many of them are done.
+            if !item.children.is_empty() {
+                let finished = item.children.iter().filter(|child| child.is_done).count();
+                ui.label(egui::RichText::new(format!("{finished}/{}", item.children.len())).weak())
+                    .on_hover_text("Sub-items done");
+            }
+            // Web addresses in the name get a button for opening them, so that
+            // clicking the name itself still checks the item.
+            for url in markup::urls(&item.name) {
+                if ui.small_button("🔗").on_hover_text(&url).clicked() {
+                    ui.ctx().output().open_url(url);
+                }
+            }
+            // Items with notes get a little arrow to show or hide them.
+            if !item.notes.is_empty()
+                && ui
+                    .small_button(if item.notes_open { "▾" } else { "▸" })
+                    .on_hover_text("Notes")
+                    .clicked()
+            {
+                item.notes_open = !item.notes_open;
+            }
+            if let Some(home) = home {
+                *go_home |= ui
+                    .small_button(format!("↗ {home}"))
+                    .on_hover_text("Show in list")
+                    .clicked();
+            }
+            resp
+        })
+        .inner;
+    // Show the due date at the far end of the row, in red if it's passed and
+    // the item isn't done.
+    if let Some(due) = item.due {
+        let (x, align) = if rtl {
+            (ui.max_rect().left(), egui::Align2::LEFT_CENTER)
+        } else {
+            (ui.max_rect().right(), egui::Align2::RIGHT_CENTER)
+        };
+        let color = if due < today && !item.is_done {
+            egui::Color32::RED
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        ui.painter().text(
+            egui::pos2(x, resp.rect.center().y),
+            align,
+            due::label(due, today),
+            egui::TextStyle::Body.resolve(ui.style()),
+            color,
+        );
+    }
+    resp
+}
+
+/// The visuals for `theme`. `prefer_dark` is what the system prefers, if that's known.
+fn theme_visuals(theme: Theme, prefer_dark: Option<bool>) -> egui::Visuals {
+    match (theme, prefer_dark) {
+        // Default to dark when the system theme can't be detected.
+        (Theme::Dark, _) | (Theme::System, Some(true) | None) => egui::Visuals::dark(),
+        (Theme::Light, _) | (Theme::System, Some(false)) => egui::Visuals::light(),
+    }
+}
+
+/// The frame drawn around the contents of the (undecorated) window.
+fn window_frame(style: &egui::Style) -> egui::Frame {
+    // Round the corners of the window.
+    egui::containers::Frame::window(style)
+        .rounding(10.0)
+        // Disable the shadow effect.
+        .shadow(epaint::Shadow {
+            extrusion: 0.0,
+            color: egui::Color32::TRANSPARENT,
+        })
+}
+
+/// A change that moves items or lists around, applied once the lists are done
+/// being drawn. Lists and items are kept by id, since deleting or moving lists
+/// in the same frame shifts them around.
+enum Convert {
+    /// Turn an item into a list of its own, placed right after its current list.
+    ItemToList { list: Uuid, item: Uuid },
+    /// Turn a list into an item at the end of another list.
+    ListToItem { list: Uuid, target: Uuid },
+    /// Move an item to the end of another list.
+    MoveItem {
+        list: Uuid,
+        item: Uuid,
+        target: Uuid,
+    },
+    /// Copy a list, placing the copy right after it.
+    DuplicateList { list: Uuid },
+    /// Delete the completed items in a list.
+    DeleteCompleted { list: Uuid },
+}
+
+impl Convert {
+    /// Make the change. Returns None, changing nothing, if anything it's for
+    /// is gone.
+    fn apply(
+        self,
+        lists: &mut Vec<List>,
+        undo: &mut UndoStack,
+        trash: &mut Vec<trash::Entry>,
+    ) -> Option<()> {
+        let find_list = |lists: &[List], id: Uuid| lists.iter().position(|list| list.id == id);
+        let find_item = |list: &List, id: Uuid| list.items.iter().position(|item| item.id == id);
+        match self {
+            Self::ItemToList { list, item } => {
+                let list = find_list(lists, list)?;
+                let item = find_item(&lists[list], item)?;
+                let item = lists[list].items.remove(item);
+                lists[list].duplicate = None;
+                lists[list].highlight = None;
+                let name = unique_list_name(lists, &item.name);
+                let mut new_list = item.into_list(&lists[list].name, name);
+                // The new list goes in the same folder as the old one.
+                new_list.folder = lists[list].folder.clone();
+                // Get straight to adding the steps.
+                new_list.focus_new_item = true;
+                lists.insert(list + 1, new_list);
+            }
+            Self::ListToItem { list, target } => {
+                let list = find_list(lists, list)?;
+                find_list(lists, target)?;
+                let source = lists.remove(list);
+                // Removing the list shifts everything after it.
+                let target = find_list(lists, target)?;
+                let target = &mut lists[target];
+                target.items.push(source.into_item(&target.name));
+            }
+            Self::MoveItem { list, item, target } => {
+                let list = find_list(lists, list)?;
+                let item = find_item(&lists[list], item)?;
+                let target = find_list(lists, target)?;
+                let mut item = lists[list].items.remove(item);
+                lists[list].duplicate = None;
+                lists[list].highlight = None;
+                item.moved(&lists[list].name, &lists[target].name);
+                lists[target].items.push(item);
+            }
+            Self::DuplicateList { list } => {
+                let list = find_list(lists, list)?;
+                let copy = lists[list].copy();
+                lists.insert(list + 1, copy);
+            }
+            Self::DeleteCompleted { list } => {
+                let list = find_list(lists, list)?;
+                lists[list].delete_completed(undo, trash);
+            }
         }
+        Some(())
     }
 }
 
+#[derive(Clone, Copy)]
+/// Something that's waiting for the user to confirm deleting it.
+enum PendingDelete {
+    /// The list with this id, along with everything in it.
+    List(Uuid),
+    /// The completed items in the list with this id.
+    Completed(Uuid),
+    /// The item with this id, along with its sub-items.
+    Item(Uuid),
+}
+
 // The state of the app.
 struct Todoish {
     /// The contents of the text box used to create a new list.
     new_list_name: String,
+    /// The contents of the search box. Only items containing it are shown.
+    search: String,
+    /// The tag picked in the row of tags under the search box, if any. Only
+    /// items with it are shown.
+    tag_filter: Option<String>,
+    /// The context picked in the dropdown under the search box, if any. Only
+    /// items that can be done there are shown.
+    context_filter: Option<String>,
     /// All of the todo lists.
     lists: Vec<List>,
+    /// Deleted items and lists, which can be restored for a while.
+    trash: Vec<trash::Entry>,
+    /// The lists as they were last saved or read, for telling what's changed since.
+    base: Vec<List>,
     /// Whether or not any lists or items have been changed.
     changed: bool,
-    /// The last time the todo list was saved.
-    last_save: time::Instant,
+    /// Writes the todo lists to disk in the background.
+    saver: Saver,
+    /// Why the last save failed, if it did.
+    save_error: Option<String>,
+    /// Keeps the quick-add hotkey registered for as long as the app is running.
+    _hotkeys: Option<GlobalHotKeyManager>,
+    /// Items captured by quick-add windows spawned from this instance.
+    captures: mpsc::Receiver<quick_add::Capture>,
+    /// The app settings.
+    settings: Settings,
+    /// Items saved for making more like them.
+    templates: Templates,
+    /// Whether or not a touch has been seen since the app started.
+    touch_seen: bool,
+    /// Whether or not the touch-friendly style is currently applied.
+    touch_active: bool,
+    /// Whether or not the system locale is written right-to-left.
+    locale_rtl: bool,
+    /// The Find & Replace dialog.
+    find_replace: FindReplace,
+    /// The palette for jumping to any list or item.
+    palette: palette::Palette,
+    /// The (list, item) indices of the item whose details are showing, if any.
+    details: Option<(usize, usize)>,
+    /// The popup for picking an item's due date, if it's showing.
+    due_editor: Option<DueEditor>,
+    /// The (list, item) indices of an item to bring into view on the next frame.
+    jump: Option<(usize, usize)>,
+    /// The id of the list shown whole while searching, after jumping to one
+    /// of its items, until the search changes.
+    in_context: Option<Uuid>,
+    /// Whether or not the window should be made at least as big as the
+    /// smallest size at the current scale, once the new scale has taken effect.
+    fit_to_scale: bool,
+    /// The current step of the first-run onboarding, if it's showing.
+    onboarding: Option<onboarding::Step>,
+    /// A message shown above the lists until it's dismissed.
+    notice: Option<String>,
+    /// The newest backup of the data file, offered alongside the notice when
+    /// the data file couldn't be read.
+    recovery: Option<PathBuf>,
+    /// Changes that can be undone (and redone).
+    undo: UndoStack,
+    /// The id and name of the item that was last swiped away, for offering to
+    /// bring it back. Touch screens don't have Ctrl+Z.
+    swiped: Option<(Uuid, String)>,
+    /// The window for importing lists from Markdown.
+    importer: markdown::Importer,
+    /// The (list, item) indices of the item selected with the keyboard.
+    focused: Option<(usize, usize)>,
+    /// The last time recurring items and lists were checked for resetting.
+    last_sweep: time::Instant,
+    /// When snoozed items were last checked on.
+    last_wake: time::Instant,
+    /// When due items were last checked for notifications.
+    last_notify: time::Instant,
+    /// When the data file was last checked for changes made elsewhere.
+    last_sync: time::Instant,
+    /// What's waiting for the user to confirm deleting it.
+    pending_delete: Option<PendingDelete>,
+    /// The items picked out with Ctrl+click or Shift+click.
+    selection: Selection,
+    /// Whether or not the pointer was over the window last frame.
+    pointer_inside: bool,
+    /// Whether or not the stats are showing instead of the lists.
+    stats_open: bool,
+    /// Whether or not closing the window was held up because saving failed,
+    /// so that closing it again quits anyway.
+    close_refused: bool,
 }
 
 impl Todoish {
     fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // Use the system setting to determine the theme. Default to dark when
-        // the theme can't be detected.
-        match cc.integration_info.prefer_dark_mode {
-            Some(true) | None => cc.egui_ctx.set_visuals(egui::Visuals::dark()),
-            Some(false) => cc.egui_ctx.set_visuals(egui::Visuals::light()),
+        // Listen for the quick-add hotkey. Not every platform supports global
+        // hotkeys, so the app should still work without one.
+        let (capture_tx, captures) = mpsc::channel();
+        let hotkeys = quick_add::register_hotkey(&cc.egui_ctx, capture_tx.clone());
+        // Quick-add windows opened some other way hand their items over too.
+        quick_add::listen(&cc.egui_ctx, capture_tx);
+
+        let settings = Settings::load();
+        // Use the theme setting, or the system one if that's what it says.
+        cc.egui_ctx.set_visuals(theme_visuals(
+            settings.theme,
+            cc.integration_info.prefer_dark_mode,
+        ));
+        // Only show the onboarding to people who have never used the app, which
+        // is anyone without any saved lists.
+        let first_run = !settings.onboarded && !data_path().exists();
+        let touch_active = settings.touch_mode == TouchMode::On;
+        touch::apply_style(&cc.egui_ctx, touch_active);
+        mini::apply_style(&cc.egui_ctx, settings.mini_mode);
+        // Scale everything before the first frame, so it's never drawn at the wrong size.
+        scale::apply(
+            &cc.egui_ctx,
+            cc.integration_info.native_pixels_per_point,
+            settings.ui_scale,
+        );
+
+        // Attempt to open ~/.todoish and deserialize. If it can't be read, let the
+        // user know where the old data went.
+        let loaded = load_lists();
+        // A broken data file can be swapped for the newest backup. One that's
+        // locked or too new is left alone, since the backups would be too.
+        let recovery = (loaded.backup.is_some() && !loaded.too_new && loaded.locked.is_none())
+            .then(persistence::latest_backup)
+            .flatten();
+        let notice = loaded.backup.map(|backup| {
+            format!(
+                "{} {}",
+                if let Some(err) = &loaded.locked {
+                    format!("Your lists couldn't be decrypted ({err}), so todoish started fresh.")
+                } else if loaded.too_new {
+                    "Your lists were saved by a newer version of todoish, so this one started fresh."
+                        .into()
+                } else if loaded.lists.is_empty() {
+                    "Your lists couldn't be read, so todoish started fresh.".into()
+                } else {
+                    "Some of your lists couldn't be read, so only the rest were loaded.".into()
+                },
+                match backup {
+                    Ok(backup) => format!("The old file was kept at {}.", backup.display()),
+                    Err(err) => format!(
+                        "The old file couldn't be moved out of the way ({err}), so move {} \
+                         somewhere safe before making any changes.",
+                        persistence::data_path().display()
+                    ),
+                }
+            )
+        });
+
+        let notice = [
+            settings::settings_path_problem(),
+            persistence::data_path_problem(),
+            notice,
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|notice, more| format!("{notice} {more}"));
+
+        // Uncheck any recurring items that came due while the app was closed.
+        let base = loaded.lists.clone();
+        let mut lists = loaded.lists;
+        let mut swept = false;
+        for list in &mut lists {
+            swept |= recurrence::sweep(&mut list.items, Local::now());
+            swept |= recurrence::sweep_list(list, Local::now());
+        }
+        // Let the user know about anything that came due while the app was closed.
+        if settings.notifications {
+            swept |= notify::check(&mut lists, due::today());
         }
+        notify::keep_awake(cc.egui_ctx.clone());
+        // Empty out anything that's been in the trash for too long.
+        let mut trash = loaded.trash;
+        trash::purge(&mut trash, &lists, Local::now());
 
-        // Attempt to open ~/.todoish and deserialize.
         Self {
             new_list_name: String::new(),
-            lists: {
-                let mut path = home_dir().expect("Failed to find home directory");
-                path.push(".todoish");
-                // Default to an empty Vec if the file doesn't exist.
-                fs::read(path).map_or(Vec::new(), |bytes| {
-                    // Panic if deserialization fails.
-                    serde_json::from_slice(&bytes).expect("JSON was incorrectly formatted")
-                })
-            },
-            changed: false,
-            last_save: time::Instant::now(),
+            search: String::new(),
+            tag_filter: None,
+            context_filter: None,
+            base,
+            lists,
+            trash,
+            changed: loaded.needs_save || swept,
+            saver: Saver::new(cc.egui_ctx.clone()),
+            save_error: None,
+            _hotkeys: hotkeys,
+            captures,
+            settings,
+            templates: Templates::load(),
+            touch_seen: false,
+            touch_active,
+            locale_rtl: rtl::locale_is_rtl(),
+            find_replace: FindReplace::default(),
+            palette: palette::Palette::default(),
+            details: None,
+            due_editor: None,
+            jump: None,
+            in_context: None,
+            fit_to_scale: false,
+            onboarding: first_run.then_some(onboarding::Step::Welcome),
+            notice,
+            recovery,
+            undo: UndoStack::default(),
+            swiped: None,
+            importer: markdown::Importer::default(),
+            focused: None,
+            last_sweep: time::Instant::now(),
+            last_wake: time::Instant::now(),
+            last_notify: time::Instant::now(),
+            last_sync: time::Instant::now(),
+            pending_delete: None,
+            selection: Selection::default(),
+            pointer_inside: false,
+            stats_open: false,
+            close_refused: false,
+        }
+    }
+
+    /// Merge in any changes made to the data file from elsewhere, like another
+    /// computer syncing it over. Returns whether or not there were any.
+    fn merge_from_disk(&mut self) -> bool {
+        let (lists, trash) = match self.saver.changes() {
+            Some(changes) => changes,
+            None => return false,
+        };
+        let base = self.base.clone();
+        self.merge_in(&base, lists, trash);
+        true
+    }
+
+    /// Merge `lists` (and `trash`), which were changed elsewhere since `base`,
+    /// into ours.
+    fn merge_in(&mut self, base: &[List], lists: Vec<List>, trash: Vec<trash::Entry>) {
+        sync::stamp(&mut self.lists, &self.base, Local::now());
+        let merged = sync::merge(base, &self.lists, &lists);
+        // Only write it back if there's something they don't have yet, so that
+        // two copies of todoish don't keep answering each other's saves.
+        let extra_trash = trash::merge(&mut self.trash, trash);
+        self.changed |= extra_trash || !sync::same(&merged, &lists);
+        self.lists = merged;
+        self.base = lists;
+        // Anything pointing into the lists by index might be wrong now.
+        self.details = None;
+        self.due_editor = None;
+        self.focused = None;
+        self.jump = None;
+        self.selection.clear();
+        self.undo = UndoStack::default();
+    }
+
+    /// Replace the lists (and the trash) with the ones in the backup at `path`.
+    fn restore_backup(&mut self, path: &Path) {
+        match persistence::read_backup(path) {
+            Ok((lists, trash)) => {
+                self.lists = lists;
+                self.trash = trash;
+                self.changed = true;
+                // Anything pointing into the lists by index is wrong now.
+                self.details = None;
+                self.due_editor = None;
+                self.focused = None;
+                self.jump = None;
+                self.selection.clear();
+                self.undo = UndoStack::default();
+                self.notice = Some(format!("Restored your lists from {}.", path.display()));
+            }
+            Err(err) => self.notice = Some(format!("The backup couldn't be restored: {err}")),
+        }
+    }
+
+    /// Note which items changed since the last save, and get ready for the next one.
+    fn stamp(&mut self) {
+        sync::stamp(&mut self.lists, &self.base, Local::now());
+        self.base = self.lists.clone();
+    }
+
+    /// Save any changes right away, without waiting for things to settle.
+    fn save_now(&mut self) -> io::Result<()> {
+        self.merge_from_disk();
+        if !self.changed && !self.saver.pending() {
+            return Ok(());
+        }
+        let base = self.base.clone();
+        self.stamp();
+        let merged =
+            self.saver
+                .save_now(self.lists.clone(), self.trash.clone(), base, &self.settings)?;
+        self.changed = false;
+        // Whatever was merged in on the way is already on disk.
+        if let Some(merged) = merged {
+            self.merge_in(&merged.sent, merged.lists, merged.trash);
         }
+        self.save_error = None;
+        Ok(())
     }
 }
 
@@ -103,222 +1308,2822 @@ impl eframe::App for Todoish {
         egui::Rgba::TRANSPARENT
     }
 
+    /// Let eframe remember where the window was and how big it was, and put
+    /// it back there next time.
+    fn persist_native_window(&self) -> bool {
+        true
+    }
+
+    /// Everything else is saved by the app itself, and lists start out open.
+    fn persist_egui_memory(&self) -> bool {
+        false
+    }
+
+    /// Save before the window closes, like from the taskbar. If that doesn't
+    /// work, the window stays open with a notice instead of losing the changes,
+    /// and closing it again quits anyway.
+    fn on_exit_event(&mut self) -> bool {
+        if self.close_refused {
+            return true;
+        }
+        match self.save_now() {
+            Ok(()) => true,
+            Err(err) => {
+                self.notice = Some(format!(
+                    "Couldn't save before closing: {err}. Close again to quit anyway."
+                ));
+                self.save_error = Some(err.to_string());
+                self.close_refused = true;
+                false
+            }
+        }
+    }
+
+    /// Save anything that hasn't been yet on the way out.
+    fn on_exit(&mut self, _: &eframe::glow::Context) {
+        quick_add::stop_listening();
+        if let Err(err) = self.save_now() {
+            eprintln!("Failed to write to disk: {err}");
+        }
+        // Remember the size of the window for next time.
+        if let Err(err) = self.settings.save() {
+            eprintln!("Failed to save settings: {err}");
+        }
+    }
+
     /// Paint the frame!
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        // Round the corners of the window.
-        let panel_frame = egui::containers::Frame::window(&ctx.style())
-            .rounding(10.0)
-            // Disable the shadow effect.
-            .shadow(epaint::Shadow {
-                extrusion: 0.0,
-                color: egui::Color32::TRANSPARENT,
-            });
+        // Ctrl+Z undoes the last change, and Ctrl+Shift+Z redoes it. (Cmd on macOS.)
+        // Text boxes have their own undo, so leave the keys to them while typing.
+        let (undo, redo) = {
+            let input = ctx.input();
+            let z = input.modifiers.command && input.key_pressed(egui::Key::Z);
+            (z && !input.modifiers.shift, z && input.modifiers.shift)
+        };
+        if !ctx.wants_keyboard_input()
+            && ((undo && self.undo.undo(&mut self.lists))
+                || (redo && self.undo.redo(&mut self.lists)))
+        {
+            self.changed = true;
+            // Whatever the popups and the keyboard were pointing at might have moved.
+            self.details = None;
+            self.due_editor = None;
+            self.focused = None;
+            self.selection.clear();
+        }
+
+        // Move items that were pinned last frame to the top, and items that were checked
+        // to the bottom in the lists that want that. Waiting until now means nothing moves
+        // out from under the pointer.
+        for (list_idx, list) in self.lists.iter_mut().enumerate() {
+            if let Some(moved) = list.sort_pinned_and_done() {
+                // Keep pointing at the same items.
+                for (list, item) in [&mut self.focused, &mut self.details].into_iter().flatten() {
+                    if *list == list_idx {
+                        *item = moved[*item];
+                    }
+                }
+                if let Some(editor) = self.due_editor.as_mut().filter(|e| e.list == list_idx) {
+                    editor.item = moved[editor.item];
+                }
+                self.changed = true;
+            }
+        }
+
+        // If the last save didn't make it to disk, try again on the next save tick.
+        // Saves that had to be merged with changes from elsewhere bring those
+        // changes back here too.
+        let (finished, merged) = self.saver.finished();
+        for merged in merged {
+            self.merge_in(&merged.sent, merged.lists, merged.trash);
+        }
+        match finished {
+            Some(Ok(())) => self.save_error = None,
+            Some(Err(err)) => {
+                // Trouble with the keyring is worth more than a red label, since
+                // it's not going to fix itself.
+                if self.settings.encrypt && self.save_error.is_none() {
+                    self.notice = Some(format!("Your lists weren't saved: {err}"));
+                }
+                self.save_error = Some(err.to_string());
+                self.changed = true;
+            }
+            None => {}
+        }
+
+        // Check on recurring items and lists every minute while the app is
+        // running, so that lists reset close to their hour.
+        if self.last_sweep.elapsed().as_secs() >= 60 {
+            for list in &mut self.lists {
+                self.changed |= recurrence::sweep(&mut list.items, Local::now());
+                self.changed |= recurrence::sweep_list(list, Local::now());
+            }
+            self.last_sweep = time::Instant::now();
+        }
+        // Snoozes are checked on more often, since they can end at any minute.
+        if self.last_wake.elapsed().as_secs() >= 1 {
+            for list in &mut self.lists {
+                self.changed |= snooze::wake(&mut list.items, Local::now());
+            }
+            self.last_wake = time::Instant::now();
+        }
+        // And on due items every minute, for notifications.
+        if self.last_notify.elapsed() >= notify::CHECK_INTERVAL {
+            if self.settings.notifications {
+                self.changed |= notify::check(&mut self.lists, due::today());
+            }
+            self.last_notify = time::Instant::now();
+        }
+
+        // The keyboard shortcuts are read now, before any text box can take focus
+        // this frame, but used once we know which items are showing.
+        let keys = keyboard::Keys::read(ctx);
+        // Ctrl+Plus, Ctrl+Minus, Ctrl+scroll and Ctrl+0 change the scale of everything.
+        let mut new_scale = scale::read_keys(ctx, self.settings.ui_scale);
+        // The smallest window size is only set at launch, so after scaling up
+        // the window is grown here instead, the frame after the scale changed.
+        if std::mem::take(&mut self.fit_to_scale) {
+            let size = ctx.input().screen_rect().size();
+            if size.x < mini::SIZE.x || size.y < mini::SIZE.y {
+                frame.set_window_size(size.max(mini::SIZE));
+            }
+        }
+
+        // Add anything that was captured by a quick-add window since the last frame.
+        while let Ok(capture) = self.captures.try_recv() {
+            capture.add_to(&mut self.lists);
+            self.changed = true;
+        }
+
+        // Switch to (or away from) the touch-friendly style when needed.
+        self.touch_seen |= ctx.input().any_touches();
+        let touch = match self.settings.touch_mode {
+            TouchMode::Auto => self.touch_seen,
+            TouchMode::On => true,
+            TouchMode::Off => false,
+        };
+        if touch != self.touch_active {
+            touch::apply_style(ctx, touch);
+            self.touch_active = touch;
+        }
+        let swipe_enabled = self.settings.swipe_gestures;
+        let mini = self.settings.mini_mode;
+        // Keep track of the size of the window, so it opens the same size next time.
+        if !mini {
+            let size = ctx.input().screen_rect().size();
+            self.settings.normal_size = Some([size.x, size.y]);
+        }
+        let warn_duplicates = self.settings.warn_duplicates;
+        let show_age = self.settings.show_age;
+        let today = due::today();
+        let clock = Local::now();
+        let now = ctx.input().time;
+        let rtl = match self.settings.layout_direction {
+            LayoutDirection::Auto => self.locale_rtl,
+            LayoutDirection::LeftToRight => false,
+            LayoutDirection::RightToLeft => true,
+        };
+        let mut settings_changed = false;
+        let mut quit = false;
+        let modifiers = ctx.input().modifiers;
+        // The (list, item) indices of an item to bring into view.
+        let jump = self.jump.take();
+        // Jumping to an item while searching shows the whole of its list, but
+        // keeps the search, so it's still there to go back to.
+        if let Some((list, _)) = jump {
+            if !self.search.trim().is_empty() {
+                self.in_context = Some(self.lists[list].id);
+            }
+        }
+
+        // The (list, item) indices of every item drawn, from top to bottom.
+        let mut visible = Vec::new();
+        // The area covered by each list, used to figure out where things are dropped.
+        let mut list_rects = Vec::with_capacity(self.lists.len());
+        // Where the things pointed at by the onboarding tour are.
+        let mut targets = onboarding::Targets::default();
 
         egui::CentralPanel::default()
-            .frame(panel_frame)
+            .frame(window_frame(&ctx.style()))
             .show(ctx, |ui| {
                 {
                     // A fake window title to prevent the app from being closed accidentally.
+                    // The title bar is taller in touch mode so that it can be grabbed with a finger.
                     let (rect, resp) = ui.allocate_at_least(
-                        egui::vec2(ui.available_width(), 15.0),
-                        egui::Sense::drag(),
+                        egui::vec2(ui.available_width(), if touch { 30.0 } else { 15.0 }),
+                        egui::Sense::click_and_drag(),
                     );
 
-                    // We still want to be able to drag the window around!
-                    if resp.dragged() {
+                    // Right-to-left layouts swap both sides of the header.
+                    let (start, end) = if rtl {
+                        (egui::Layout::right_to_left(), egui::Layout::left_to_right())
+                    } else {
+                        (egui::Layout::left_to_right(), egui::Layout::right_to_left())
+                    };
+                    let mut title_bar = ui.child_ui(rect, start);
+                    // Show "todoish" at the start of the header.
+                    title_bar.label("todoish");
+                    // Show whether or not the changes have been saved at the end of the header.
+                    let buttons = title_bar.with_layout(end, |ui| {
+                        // A close button at the very end. It takes a double-click, so
+                        // that the window still can't be closed by accident.
+                        let close = ui
+                            .add(
+                                egui::Button::new(egui::RichText::new("×").weak())
+                                    .frame(false)
+                                    .small(),
+                            )
+                            .on_hover_text("Double-click to save and quit");
+                        if close.double_clicked() {
+                            quit = true;
+                        }
+                        // A button for switching between the lists and the stats.
+                        let stats = ui
+                            .add(
+                                egui::Button::new(egui::RichText::new("📊").weak())
+                                    .frame(false)
+                                    .small(),
+                            )
+                            .on_hover_text(if self.stats_open {
+                                "Back to the lists"
+                            } else {
+                                "Stats"
+                            });
+                        if stats.clicked() {
+                            self.stats_open = !self.stats_open;
+                        }
+                        // A button for hiding completed items from every list.
+                        let hide = self.settings.hide_completed;
+                        let eye = ui
+                            .add(
+                                egui::Button::new(egui::RichText::new("👁").color(if hide {
+                                    ui.visuals().weak_text_color()
+                                } else {
+                                    ui.visuals().text_color()
+                                }))
+                                .frame(false)
+                                .small(),
+                            )
+                            .on_hover_text(if hide {
+                                "Show completed items"
+                            } else {
+                                "Hide completed items"
+                            });
+                        if eye.clicked() {
+                            self.settings.hide_completed = !hide;
+                            settings_changed = true;
+                        }
+                        // A failed save can be tried again right away by clicking it.
+                        let resp = match &self.save_error {
+                            Some(err) => ui
+                                .add(
+                                    egui::Label::new(
+                                        egui::RichText::new("save failed ↻")
+                                            .color(egui::Color32::RED),
+                                    )
+                                    .sense(egui::Sense::click()),
+                                )
+                                .on_hover_ui(|ui| {
+                                    ui.label(format!(
+                                        "Couldn't save to {}: {err}",
+                                        data_path().display()
+                                    ));
+                                    ui.label("Click to try again.");
+                                }),
+                            None => {
+                                let unsaved = self.changed || self.saver.pending();
+                                let text =
+                                    egui::RichText::new(if unsaved { "unsaved" } else { "saved" })
+                                        .weak();
+                                ui.label(text).on_hover_ui(|ui| {
+                                    ui.label(format!("Saved to {}", data_path().display()));
+                                })
+                            }
+                        };
+                        targets.saved = Some(resp.rect);
+                        if self.save_error.is_some() && resp.clicked() {
+                            if let Err(err) = self.save_now() {
+                                self.save_error = Some(err.to_string());
+                            }
+                        }
+                        close.hovered() || stats.hovered() || eye.hovered()
+                    });
+
+                    // We still want to be able to drag the window around, just not
+                    // by the buttons.
+                    if resp.dragged() && !buttons.inner {
                         frame.drag_window();
                     }
 
-                    let mut title_bar = ui.child_ui(rect, egui::Layout::left_to_right());
-                    // Show "todoish" on the left of the header.
-                    title_bar.label("todoish");
-                    // Show whether or not the changes have been saved on the right of the header.
-                    title_bar.with_layout(egui::Layout::right_to_left(), |ui| {
-                        let text =
-                            egui::RichText::new(if self.changed { "unsaved" } else { "saved" })
-                                .weak();
-                        ui.label(text);
+                    // Settings live in a context menu on the title bar.
+                    touch::context_menu(ui, &resp, touch, |ui| {
+                        ui.menu_button("Touch mode", |ui| {
+                            for (mode, label) in [
+                                (TouchMode::Auto, "Automatic"),
+                                (TouchMode::On, "On"),
+                                (TouchMode::Off, "Off"),
+                            ] {
+                                if ui
+                                    .radio_value(&mut self.settings.touch_mode, mode, label)
+                                    .clicked()
+                                {
+                                    settings_changed = true;
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        ui.menu_button("Theme", |ui| {
+                            for (theme, label) in [
+                                (Theme::System, "System"),
+                                (Theme::Light, "Light"),
+                                (Theme::Dark, "Dark"),
+                            ] {
+                                if ui
+                                    .radio_value(&mut self.settings.theme, theme, label)
+                                    .clicked()
+                                {
+                                    ui.ctx().set_visuals(theme_visuals(
+                                        theme,
+                                        frame.info().prefer_dark_mode,
+                                    ));
+                                    settings_changed = true;
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        ui.menu_button("Layout direction", |ui| {
+                            for (direction, label) in [
+                                (LayoutDirection::Auto, "Automatic"),
+                                (LayoutDirection::LeftToRight, "Left to right"),
+                                (LayoutDirection::RightToLeft, "Right to left"),
+                            ] {
+                                if ui
+                                    .radio_value(
+                                        &mut self.settings.layout_direction,
+                                        direction,
+                                        label,
+                                    )
+                                    .clicked()
+                                {
+                                    settings_changed = true;
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        if ui
+                            .checkbox(
+                                &mut self.settings.swipe_gestures,
+                                "Swipe to complete/delete",
+                            )
+                            .changed()
+                        {
+                            settings_changed = true;
+                            ui.close_menu();
+                        }
+                        if ui
+                            .checkbox(
+                                &mut self.settings.warn_duplicates,
+                                "Warn about duplicate items",
+                            )
+                            .changed()
+                        {
+                            settings_changed = true;
+                            ui.close_menu();
+                        }
+                        if ui
+                            .checkbox(
+                                &mut self.settings.notifications,
+                                "Notify when items are due",
+                            )
+                            .changed()
+                        {
+                            settings_changed = true;
+                            ui.close_menu();
+                        }
+                        if ui
+                            .checkbox(&mut self.settings.show_age, "Show how old items are")
+                            .changed()
+                        {
+                            settings_changed = true;
+                            ui.close_menu();
+                        }
+                        // Mini mode shrinks everything down to sit in a corner. The
+                        // window can only be kept on top from the start, so that part
+                        // waits for a restart.
+                        let mut mini = self.settings.mini_mode;
+                        if ui
+                            .checkbox(&mut mini, "Mini mode (on top after restart)")
+                            .on_hover_text(
+                                "A small window that stays on top of the others. \
+                                 It shrinks right away, but only stays on top \
+                                 once todoish has been restarted.",
+                            )
+                            .changed()
+                        {
+                            if mini {
+                                let size = ui.ctx().input().screen_rect().size();
+                                self.settings.normal_size = Some([size.x, size.y]);
+                                frame.set_window_size(mini::SIZE);
+                            } else {
+                                let size = self
+                                    .settings
+                                    .normal_size
+                                    .map_or(mini::NORMAL_SIZE, |[x, y]| egui::vec2(x, y));
+                                frame.set_window_size(size);
+                            }
+                            mini::apply_style(ui.ctx(), mini);
+                            self.settings.mini_mode = mini;
+                            settings_changed = true;
+                            ui.close_menu();
+                        }
+                        // Make sure the keyring works before promising encryption.
+                        let mut encrypt = self.settings.encrypt;
+                        if ui
+                            .checkbox(&mut encrypt, "Encrypt data file")
+                            .on_hover_text("The key is kept in your system's keyring.")
+                            .changed()
+                        {
+                            match encrypt.then(|| crypto::key(true)).transpose() {
+                                Ok(_) => {
+                                    self.settings.encrypt = encrypt;
+                                    settings_changed = true;
+                                    // Rewrite the file the new way.
+                                    self.changed = true;
+                                }
+                                Err(err) => self.notice = Some(err),
+                            }
+                            ui.close_menu();
+                        }
+                        // How many old copies of the data file are kept.
+                        ui.horizontal(|ui| {
+                            ui.label("Backups to keep");
+                            if ui
+                                .add(
+                                    egui::DragValue::new(&mut self.settings.keep_backups)
+                                        .clamp_range(0..=100),
+                                )
+                                .on_hover_text("Taken at most once an hour, while saving.")
+                                .changed()
+                            {
+                                settings_changed = true;
+                            }
+                        });
+                        // Buttons for scaling everything up or down.
+                        ui.horizontal(|ui| {
+                            let scale = self.settings.ui_scale;
+                            ui.label("Zoom");
+                            if ui.small_button("-").clicked() {
+                                new_scale = Some(scale::clamp(scale - scale::STEP));
+                            }
+                            ui.label(format!("{:.0}%", scale * 100.0));
+                            if ui.small_button("+").clicked() {
+                                new_scale = Some(scale::clamp(scale + scale::STEP));
+                            }
+                            if ui.small_button("Reset").clicked() {
+                                new_scale = Some(1.0);
+                            }
+                        });
+                        ui.separator();
+                        if ui.button("Export all as Markdown").clicked() {
+                            ui.output().copied_text = markdown::export(&self.lists);
+                            ui.close_menu();
+                        }
+                        if ui.button("Import from Markdown…").clicked() {
+                            self.importer.open = true;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Quit").clicked() {
+                            quit = true;
+                            ui.close_menu();
+                        }
                     });
                 }
 
                 ui.separator();
 
+                // Show the notice, if there is one, until it's dismissed.
+                if let Some(notice) = &self.notice {
+                    let mut dismissed = false;
+                    let mut restore = false;
+                    egui::Frame::group(ui.style())
+                        .fill(ui.visuals().extreme_bg_color)
+                        .stroke(egui::Stroke::new(1.0, egui::Color32::GOLD))
+                        .show(ui, |ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.colored_label(egui::Color32::GOLD, "⚠");
+                                ui.label(notice);
+                                if let Some(path) = &self.recovery {
+                                    restore = ui
+                                        .button("Restore latest backup")
+                                        .on_hover_text(format!(
+                                            "Replace the lists with the ones in {}",
+                                            path.display()
+                                        ))
+                                        .clicked();
+                                }
+                                dismissed = ui.button("Dismiss").clicked();
+                            });
+                        });
+                    if restore {
+                        if let Some(path) = self.recovery.take() {
+                            self.restore_backup(&path);
+                        }
+                    } else if dismissed {
+                        self.notice = None;
+                        self.recovery = None;
+                    }
+                }
+
+                // Offer to bring back an item that was swiped away, for as long as
+                // that's still the last thing that can be undone.
+                if let Some((id, name)) = &self.swiped {
+                    if self.undo.last_deleted(*id) {
+                        let mut undo = false;
+                        let mut dismissed = false;
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(format!("Deleted \"{name}\""));
+                            undo = ui.button("Undo").clicked();
+                            dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                        });
+                        if undo && self.undo.undo(&mut self.lists) {
+                            self.changed = true;
+                            self.details = None;
+                            self.due_editor = None;
+                            self.focused = None;
+                            self.selection.clear();
+                        }
+                        if undo || dismissed {
+                            self.swiped = None;
+                        }
+                    } else {
+                        self.swiped = None;
+                    }
+                }
+
+                // The stats take the place of the lists while they're open.
+                if self.stats_open {
+                    if keys.escape {
+                        self.stats_open = false;
+                    }
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        stats::show(ui, &self.lists, today);
+                    });
+                    return;
+                }
+
                 {
-                    // The text box for creating a new todo list.
-                    let resp = egui::TextEdit::singleline(&mut self.new_list_name)
-                        .hint_text("new list")
-                        .desired_width(ui.available_width())
-                        .show(ui)
-                        .response;
+                    // The text box for creating a new todo list, after a menu for
+                    // making one from a template instead.
+                    let resp = ui
+                        .horizontal(|ui| {
+                            ui.menu_button("📋", |ui| {
+                                if let Some(list) = self.templates.list_menu(ui, &self.lists) {
+                                    self.lists.push(list);
+                                    self.changed = true;
+                                }
+                            })
+                            .response
+                            .on_hover_text("New list from a template");
+                            egui::TextEdit::singleline(&mut self.new_list_name)
+                                .hint_text("new list, or List: item")
+                                .desired_width(ui.available_width())
+                                .show(ui)
+                                .response
+                        })
+                        .inner;
+                    targets.new_list = Some(resp.rect);
+
+                    // Pasting several lines makes a list out of them. A first line
+                    // like "Groceries:" names the list, and otherwise they go in the inbox.
+                    if self.new_list_name.contains('\n') {
+                        let text = std::mem::take(&mut self.new_list_name);
+                        let (name, lines) = markdown::split_list(&text);
+                        list_named(&mut self.lists, name.unwrap_or(quick_add::INBOX))
+                            .add_lines(lines);
+                        self.changed = true;
+                    }
 
                     if resp.lost_focus() {
                         self.new_list_name = self.new_list_name.trim().into();
 
                         if ui.input().key_pressed(egui::Key::Enter) {
-                            self.lists.push(List::new(self.new_list_name.clone()));
+                            let parsed = quick_add::parse_quick_add(&self.new_list_name);
+                            match parsed.list {
+                                // "Groceries: milk" adds milk to the Groceries list
+                                // (making the list if it doesn't exist yet).
+                                Some(list) if !parsed.name.is_empty() => {
+                                    let mut item = Item::new(parsed.name);
+                                    item.set_important(parsed.is_important);
+                                    list_named(&mut self.lists, &list).items.push(item);
+                                    self.changed = true;
+                                }
+                                // Lists need names.
+                                None if self.new_list_name.is_empty() => {}
+                                // There's already a list with that name, so open it up
+                                // and move to its new item box instead of making another.
+                                // "Groceries:" with nothing after it is the list itself.
+                                list => {
+                                    let name = list.unwrap_or_else(|| self.new_list_name.clone());
+                                    match find_list(&self.lists, &name) {
+                                        Some(existing) => {
+                                            self.lists[existing].focus_new_item = true
+                                        }
+                                        None => {
+                                            self.lists.push(List::new(name));
+                                            self.changed = true;
+                                        }
+                                    }
+                                }
+                            }
                             self.new_list_name = String::new();
-                            self.changed = true;
                         }
                     }
                 }
+                {
+                    // The text box for searching through every list.
+                    let resp = egui::TextEdit::singleline(&mut self.search)
+                        .hint_text("search")
+                        .desired_width(ui.available_width())
+                        .show(ui)
+                        .response;
+                    // Escape clears the search, whether or not the box has focus.
+                    let escape = ui.input().key_pressed(egui::Key::Escape);
+                    if escape && (resp.lost_focus() || !ui.ctx().wants_keyboard_input()) {
+                        self.search.clear();
+                    }
+                    // Searching for something else goes back to the results.
+                    if resp.changed() || self.search.trim().is_empty() {
+                        self.in_context = None;
+                    }
+                }
+                // Tags can be filtered by once any are in use, along with
+                // whichever one is picked, even if nothing has it anymore.
+                let mut tags_in_use = tag::all(&self.lists);
+                if let Some(tag) = &self.tag_filter {
+                    if !tags_in_use.contains(tag) {
+                        tags_in_use.push(tag.clone());
+                    }
+                }
+                // So can contexts, along with whichever one is picked, even
+                // if nothing's in it anymore.
+                let mut contexts = context::all(&self.lists);
+                if let Some(context) = &self.context_filter {
+                    if !contexts.contains(context) {
+                        contexts.push(context.clone());
+                    }
+                }
+                if !tags_in_use.is_empty() || !contexts.is_empty() {
+                    ui.horizontal(|ui| {
+                        tag::filter_row(ui, &mut self.tag_filter, &tags_in_use);
+                        if !contexts.is_empty() {
+                            context::filter_box(ui, &mut self.context_filter, &contexts);
+                        }
+                    });
+                }
+                let tag_filter = self.tag_filter.clone();
+                let context_filter = self.context_filter.clone();
+                let filtering = tag_filter.is_some() || context_filter.is_some();
+                let query = self.search.trim().to_lowercase();
+                let searching = !query.is_empty();
+                let in_context = self.in_context;
+                // Whether or not an item is let through by the search and filters.
+                let shown = |item: &Item| {
+                    (!searching || item.matches(&query))
+                        && tag_filter.as_ref().is_none_or(|tag| item.has_tag(tag))
+                        && context_filter
+                            .as_ref()
+                            .is_none_or(|context| item.has_context(context))
+                };
 
                 // Put some space between the text box and the lists. The effect
                 // is most easily noticable when scrolled down.
                 ui.allocate_space(egui::vec2(0.0, 3.0));
 
                 let mut delete = None;
+                let mut convert = None;
+                let mut rename = None;
+                let mut reorder = None;
+                let mut bulk = None;
+                let list_names: Vec<String> =
+                    self.lists.iter().map(|list| list.name.clone()).collect();
+                let list_ids: Vec<Uuid> = self.lists.iter().map(|list| list.id).collect();
+                let folders = folder::all(&self.lists);
+                let list_folders: Vec<Option<String>> =
+                    self.lists.iter().map(|list| list.folder.clone()).collect();
+                let list_groups = folder::groups(&self.lists);
+                // The id and name of every item, for linking to items in other lists
+                // while the lists themselves are being drawn.
+                let items_by_list: Vec<Vec<(Uuid, String)>> = self
+                    .lists
+                    .iter()
+                    .map(|list| {
+                        list.items
+                            .iter()
+                            .map(|item| (item.id, item.name.clone()))
+                            .collect()
+                    })
+                    .collect();
+                // The names of the items that aren't done yet, by id, for telling
+                // which items are still blocked.
+                let unfinished: HashMap<Uuid, String> = self
+                    .lists
+                    .iter()
+                    .flat_map(|list| &list.items)
+                    .filter(|item| !item.is_effectively_done())
+                    .map(|item| (item.id, item.name.clone()))
+                    .collect();
+                let locate = |id: Uuid| {
+                    items_by_list.iter().enumerate().find_map(|(list, items)| {
+                        items
+                            .iter()
+                            .position(|(item, _)| *item == id)
+                            .map(|item| (list, item))
+                    })
+                };
                 egui::ScrollArea::vertical()
                     .stick_to_bottom()
                     .show(ui, |ui| {
-                        // Loop over every list.
-                        let len = self.lists.len();
-                        for (idx, list) in self.lists.iter_mut().enumerate() {
-                            // Draw the header of this list.
-                            let resp = egui::CollapsingHeader::new(&list.name)
-                                .default_open(true)
-                                .show(ui, |ui| {
-                                    let mut delete = None;
-                                    // Loop over every item in this list.
-                                    for (idx, item) in list.items.iter_mut().enumerate() {
-                                        let resp = if item.editing {
-                                            // If the user wants to edit the name
-                                            // of this item, draw a text box instead
-                                            // of a checkbox.
-                                            let resp = ui.text_edit_singleline(&mut item.name);
-                                            // Steal focus immediately after the
-                                            // double-click event.
-                                            if item.begin_editing {
-                                                resp.request_focus();
-                                                item.begin_editing = false;
+                        ui.with_layout(
+                            egui::Layout::top_down(if rtl {
+                                egui::Align::RIGHT
+                            } else {
+                                egui::Align::LEFT
+                            }),
+                            |ui| {
+                                // Gather up what needs doing today above the lists.
+                                if !searching
+                                    && !filtering
+                                    && today::show(
+                                        ui,
+                                        &mut self.lists,
+                                        &mut self.undo,
+                                        &mut self.jump,
+                                        today,
+                                        rtl,
+                                    )
+                                {
+                                    self.changed = true;
+                                }
+                                // Loop over every list.
+                                let len = self.lists.len();
+                                let mut last_folder = None;
+                                let mut folder_open = true;
+                                for (idx, list) in self.lists.iter_mut().enumerate() {
+                                    let list_idx = idx;
+                                    // The list that was jumped to while searching is
+                                    // shown whole, to see the item in context.
+                                    let whole = in_context == Some(list.id);
+                                    // Hide lists without anything that matches the search.
+                                    // Mini mode only has room for lists with something left to do.
+                                    if ((searching || filtering)
+                                        && !whole
+                                        && !list.items.iter().any(shown))
+                                        || (mini && list.items.iter().all(|item| item.is_done))
+                                    {
+                                        list_rects.push(egui::Rect::NOTHING);
+                                        continue;
+                                    }
+                                    // Lists in a folder go under its header, which is drawn
+                                    // above the first of them. Searching and jumping to a
+                                    // list open its folder.
+                                    if list.folder != last_folder {
+                                        last_folder = list.folder.clone();
+                                        folder_open = match &list.folder {
+                                            Some(name) => {
+                                                let jumping = jump.is_some_and(|(list, _)| {
+                                                    list_folders[list] == last_folder
+                                                });
+                                                folder::header(ui, name, searching || jumping)
                                             }
-                                            // Return to a checkbox when we're
-                                            // done editing the name.
-                                            if resp.lost_focus() {
-                                                self.changed = true;
-                                                item.editing = false;
+                                            None => true,
+                                        };
+                                    }
+                                    if !folder_open {
+                                        list_rects.push(egui::Rect::NOTHING);
+                                        continue;
+                                    }
+                                    // Draw the header of this list. The arrow comes first,
+                                    // which puts it on the right in right-to-left layouts.
+                                    // Search results use their own (always open) state, so
+                                    // that the usual one is untouched when the search ends.
+                                    // Mini mode has its own state too, which starts closed.
+                                    let mut id = ui.make_persistent_id(list.id);
+                                    if searching {
+                                        id = id.with("search");
+                                    } else if mini {
+                                        id = id.with("mini");
+                                    }
+                                    let mut state = CollapsingState::load_with_default_open(
+                                        ui.ctx(),
+                                        id,
+                                        searching || !mini,
+                                    );
+                                    // Open the list to get to its new item text box.
+                                    if list.focus_new_item {
+                                        state.set_open(true);
+                                    }
+                                    // Open the list and point out the item being jumped to.
+                                    if let Some((_, item)) = jump.filter(|&(list, _)| list == idx) {
+                                        state.set_open(true);
+                                        list.highlight = Some((item, None));
+                                    }
+                                    let (done, total) = list.progress();
+                                    let effort_left = effort::remaining(&list.items);
+                                    // Colored lists wash their items in the color, underneath
+                                    // them, once it's known where they went.
+                                    let wash = ui.painter().add(epaint::Shape::Noop);
+                                    let (arrow, header, body) = state
+                                        .show_header(ui, |ui| {
+                                            let mut name = if list.starred {
+                                                egui::RichText::new(format!("★ {}", list.name))
+                                            } else {
+                                                egui::RichText::new(&list.name)
+                                            };
+                                            if let Some(color) = list.color {
+                                                name = name.color(color::tint(color, ui.visuals()));
                                             }
-                                            resp
-                                        } else {
-                                            // If we're not editing the name, just
-                                            // draw a normal checkbox instead.
-                                            let mut text = egui::RichText::new(&item.name);
-                                            // Draw the text distinctly if this item is marked as important.
-                                            if item.is_important {
-                                                text = text.underline();
+                                            let name = ui.add(
+                                                egui::Label::new(name).sense(egui::Sense::click()),
+                                            );
+                                            // Show how much of the list is done at the end of the
+                                            // header, like the saved indicator in the title bar.
+                                            if total > 0 {
+                                                let end = if rtl {
+                                                    egui::Layout::left_to_right()
+                                                } else {
+                                                    egui::Layout::right_to_left()
+                                                };
+                                                ui.with_layout(end, |ui| {
+                                                    ui.label(
+                                                        egui::RichText::new(format!(
+                                                            "{done}/{total}"
+                                                        ))
+                                                        .weak(),
+                                                    );
+                                                    // Along with how much work is left, if
+                                                    // any of it has been estimated.
+                                                    if effort_left > 0 {
+                                                        ui.label(
+                                                            egui::RichText::new(effort::label(
+                                                                effort_left,
+                                                            ))
+                                                            .weak(),
+                                                        )
+                                                        .on_hover_text("Effort left");
+                                                    }
+                                                });
                                             }
-                                            // Draw the checkbox for this item.
-                                            let resp = ui.checkbox(&mut item.is_done, text);
-                                            if resp.changed() {
-                                                self.changed = true;
+                                            name
+                                        })
+                                        .body(|ui| {
+                                            let mut delete = None;
+                                            let mut archive = None;
+                                            let hide_done = list
+                                                .show_completed
+                                                .map_or(self.settings.hide_completed, |show| !show);
+                                            // Loop over every item in this list.
+                                            for (idx, item) in list.items.iter_mut().enumerate() {
+                                                // Only show items that match the search
+                                                // and tag filter.
+                                                if !whole && !shown(item) {
+                                                    continue;
+                                                }
+                                                // Snoozed items stay out of the way unless asked for,
+                                                // or being jumped to.
+                                                let pointed_out = list
+                                                    .highlight
+                                                    .is_some_and(|(highlight, _)| highlight == idx);
+                                                if item.snoozed_until.is_some()
+                                                    && !list.show_snoozed
+                                                    && !pointed_out
+                                                {
+                                                    continue;
+                                                }
+                                                // So do completed items, if they're hidden.
+                                                if hide_done && item.is_done && !pointed_out {
+                                                    if item.hidden(now) {
+                                                        continue;
+                                                    }
+                                                    ui.ctx().request_repaint();
+                                                }
+                                                visible.push((list_idx, idx));
+                                                // Rows that are scrolled out of view only take up
+                                                // as much space as they did last time, which keeps
+                                                // long lists quick. Rows that are being edited,
+                                                // focused, or flashed are always drawn.
+                                                let row_id = egui::Id::new(("row height", item.id));
+                                                let row_top = ui.cursor().top();
+                                                let busy = item.editing
+                                                    || item.editing_notes
+                                                    || self.focused == Some((list_idx, idx))
+                                                    || list.highlight.is_some_and(
+                                                        |(highlight, _)| highlight == idx,
+                                                    );
+                                                let height = ui.data().get_temp::<f32>(row_id);
+                                                if let (false, Some(height)) = (busy, height) {
+                                                    let row = egui::Rect::from_min_size(
+                                                        ui.cursor().min,
+                                                        egui::vec2(ui.available_width(), height),
+                                                    );
+                                                    if !ui.clip_rect().intersects(row) {
+                                                        // The spacing after the row is added back.
+                                                        ui.allocate_space(egui::vec2(
+                                                            ui.available_width(),
+                                                            height - ui.spacing().item_spacing.y,
+                                                        ));
+                                                        continue;
+                                                    }
+                                                }
+                                                // Reserve a spot behind the row for the keyboard focus.
+                                                let focus_bg =
+                                                    ui.painter().add(epaint::Shape::Noop);
+                                                let resp = if item.editing {
+                                                    // If the user wants to edit the name
+                                                    // of this item, draw a text box instead
+                                                    // of a checkbox. Its id comes from the
+                                                    // item's, so that it keeps its state (and
+                                                    // the focus) if rows above it go away.
+                                                    let rename_id =
+                                                        egui::Id::new(("rename", item.id));
+                                                    // Names can have more than one line. Enter
+                                                    // finishes, and Shift+Enter starts a new line.
+                                                    let focused = ui.memory().has_focus(rename_id);
+                                                    let enter = focused
+                                                        && ui.input_mut().consume_key(
+                                                            egui::Modifiers::NONE,
+                                                            egui::Key::Enter,
+                                                        );
+                                                    let resp = ui.add(
+                                                        egui::TextEdit::multiline(
+                                                            &mut item.edit_name,
+                                                        )
+                                                        .id(rename_id)
+                                                        .desired_rows(1)
+                                                        .desired_width(ui.available_width()),
+                                                    );
+                                                    if enter {
+                                                        resp.surrender_focus();
+                                                    }
+                                                    // Steal focus immediately after the
+                                                    // double-click event.
+                                                    if item.begin_editing {
+                                                        resp.request_focus();
+                                                        item.begin_editing = false;
+                                                    }
+                                                    // Return to a checkbox when we're
+                                                    // done editing the name. Escape takes the
+                                                    // focus away too, but leaves the name alone.
+                                                    if resp.lost_focus() || enter {
+                                                        let cancelled = ui
+                                                            .input()
+                                                            .key_pressed(egui::Key::Escape);
+                                                        let edited =
+                                                            std::mem::take(&mut item.edit_name);
+                                                        if let Some(name) = finish_rename(
+                                                            &item.name, &edited, cancelled,
+                                                        ) {
+                                                            self.undo.push(Action::Rename {
+                                                                item: item.id,
+                                                                from: item.name.clone(),
+                                                                to: name.clone(),
+                                                            });
+                                                            item.rename(name);
+                                                            self.changed = true;
+                                                        }
+                                                        item.editing = false;
+                                                    }
+                                                    resp
+                                                } else {
+                                                    // If we're not editing the name, just
+                                                    // draw a normal checkbox instead, which can be
+                                                    // swiped right to complete it or left to delete it.
+                                                    let shown = item.is_effectively_done();
+                                                    let mut done = shown;
+                                                    let mut go_home = false;
+                                                    let (resp, swiped) = swipe::swipeable(
+                                                        ui,
+                                                        egui::Id::new(("swipe", item.id)),
+                                                        swipe_enabled,
+                                                        |ui| {
+                                                            let blocker = item
+                                                                .blocked_by
+                                                                .and_then(|id| unfinished.get(&id));
+                                                            // Search results can be seen in
+                                                            // context in their list.
+                                                            let home = (searching && !whole)
+                                                                .then_some(
+                                                                    list_names[list_idx].as_str(),
+                                                                );
+                                                            item_row(
+                                                                ui,
+                                                                item,
+                                                                &mut done,
+                                                                today,
+                                                                rtl,
+                                                                blocker.map(String::as_str),
+                                                                show_age,
+                                                                home,
+                                                                &mut go_home,
+                                                            )
+                                                        },
+                                                    );
+                                                    if go_home {
+                                                        self.jump = Some((list_idx, idx));
+                                                        ui.ctx().request_repaint();
+                                                    }
+                                                    match swiped {
+                                                        Some(swipe::Swipe::Right) => {
+                                                            done = true;
+                                                            self.changed = true;
+                                                        }
+                                                        Some(swipe::Swipe::Left) => {
+                                                            // Items with sub-items ask first.
+                                                            if item.children.is_empty() {
+                                                                delete = Some(idx);
+                                                                self.swiped = Some((
+                                                                    item.id,
+                                                                    item.name.clone(),
+                                                                ));
+                                                                self.changed = true;
+                                                            } else {
+                                                                self.pending_delete = Some(
+                                                                    PendingDelete::Item(item.id),
+                                                                );
+                                                            }
+                                                        }
+                                                        None => {}
+                                                    }
+                                                    // Clicking with Ctrl or Shift held selects the
+                                                    // item instead of checking it.
+                                                    if resp.clicked()
+                                                        && self.selection.click(
+                                                            list_idx, idx, item.id, modifiers,
+                                                        )
+                                                    {
+                                                        done = shown;
+                                                    }
+                                                    // Checking an item checks its sub-items too.
+                                                    if done != shown {
+                                                        self.changed = true;
+                                                        self.undo.push(item.set_done_all(done));
+                                                        item.checked_at = done.then_some(now);
+                                                    }
+                                                    if resp.double_clicked() {
+                                                        item.edit_name = item.name.clone();
+                                                        item.editing = true;
+                                                        item.begin_editing = true;
+                                                    }
+                                                    resp
+                                                };
+                                                // Clicking an item moves the keyboard focus to it.
+                                                if resp.clicked() {
+                                                    self.focused = Some((list_idx, idx));
+                                                }
+                                                // Selected items are drawn a bit stronger than
+                                                // the focused one.
+                                                let shade = if self.selection.contains(item.id) {
+                                                    Some(0.6)
+                                                } else if self.focused == Some((list_idx, idx)) {
+                                                    Some(0.3)
+                                                } else {
+                                                    None
+                                                };
+                                                if let Some(shade) = shade {
+                                                    let row = egui::Rect::from_x_y_ranges(
+                                                        ui.max_rect().x_range(),
+                                                        resp.rect.y_range(),
+                                                    );
+                                                    ui.painter().set(
+                                                        focus_bg,
+                                                        epaint::Shape::rect_filled(
+                                                            row.expand(2.0),
+                                                            4.0,
+                                                            ui.visuals()
+                                                                .selection
+                                                                .bg_fill
+                                                                .linear_multiply(shade),
+                                                        ),
+                                                    );
+                                                }
+                                                // Flash the item if it was just pointed out.
+                                                if let Some((highlight, start)) =
+                                                    &mut list.highlight
+                                                {
+                                                    if *highlight == idx {
+                                                        // Bring it into view the first time it's drawn.
+                                                        let start =
+                                                            *start.get_or_insert_with(|| {
+                                                                resp.scroll_to_me(Some(
+                                                                    egui::Align::Center,
+                                                                ));
+                                                                now
+                                                            });
+                                                        let fade =
+                                                            1.0 - ((now - start) / 1.5) as f32;
+                                                        if fade > 0.0 {
+                                                            ui.painter().rect_filled(
+                                                                resp.rect.expand(2.0),
+                                                                4.0,
+                                                                ui.visuals()
+                                                                    .selection
+                                                                    .bg_fill
+                                                                    .linear_multiply(0.5 * fade),
+                                                            );
+                                                            ui.ctx().request_repaint();
+                                                        } else {
+                                                            list.highlight = None;
+                                                        }
+                                                    }
+                                                }
+                                                // Say when a snoozed item comes back.
+                                                if let Some(until) = item.snoozed_until {
+                                                    ui.indent(("snoozed", item.id), |ui| {
+                                                        ui.label(
+                                                            egui::RichText::new(format!(
+                                                                "💤 {}",
+                                                                snooze::label(until, clock)
+                                                            ))
+                                                            .weak(),
+                                                        );
+                                                    });
+                                                }
+                                                // Draw the notes under the row, if they're showing.
+                                                if item.editing_notes {
+                                                    let resp = ui.add(
+                                                        egui::TextEdit::multiline(&mut item.notes)
+                                                            .id(egui::Id::new(("notes", item.id)))
+                                                            .hint_text("notes")
+                                                            .desired_rows(2)
+                                                            .desired_width(ui.available_width()),
+                                                    );
+                                                    if std::mem::take(&mut item.begin_editing) {
+                                                        resp.request_focus();
+                                                    }
+                                                    if resp.changed() {
+                                                        self.changed = true;
+                                                    }
+                                                    if resp.lost_focus() {
+                                                        item.notes = item.notes.trim_end().into();
+                                                        item.editing_notes = false;
+                                                    }
+                                                } else if item.notes_open && !item.notes.is_empty()
+                                                {
+                                                    ui.indent(("notes", item.id), |ui| {
+                                                        let format = egui::TextFormat {
+                                                            font_id: egui::TextStyle::Body
+                                                                .resolve(ui.style()),
+                                                            color: ui.visuals().weak_text_color(),
+                                                            ..Default::default()
+                                                        };
+                                                        let notes = markup::layout(
+                                                            &item.notes,
+                                                            format,
+                                                            ui.visuals(),
+                                                        );
+                                                        ui.add(egui::Label::new(notes).wrap(true));
+                                                    });
+                                                }
+                                                // Draw links to related items as chips under the row.
+                                                if !item.links.is_empty() {
+                                                    let mut unlink = None;
+                                                    ui.indent(("links", item.id), |ui| {
+                                                        ui.horizontal_wrapped(|ui| {
+                                                            for (n, link) in
+                                                                item.links.iter().enumerate()
+                                                            {
+                                                                match locate(*link) {
+                                                                    Some((l, i)) => {
+                                                                        let chip = ui.small_button(
+                                                                            format!(
+                                                                                "🔗 {} · {}",
+                                                                                items_by_list[l][i]
+                                                                                    .1,
+                                                                                list_names[l]
+                                                                            ),
+                                                                        );
+                                                                        // Clicking a link takes you to the item.
+                                                                        if chip.clicked() {
+                                                                            self.jump =
+                                                                                Some((l, i));
+                                                                            ui.ctx()
+                                                                                .request_repaint();
+                                                                        }
+                                                                        chip.context_menu(|ui| {
+                                                                            if ui
+                                                                                .button(
+                                                                                    "Remove link",
+                                                                                )
+                                                                                .clicked()
+                                                                            {
+                                                                                unlink = Some(n);
+                                                                                ui.close_menu();
+                                                                            }
+                                                                        });
+                                                                    }
+                                                                    // The item has been deleted.
+                                                                    None => {
+                                                                        ui.label(
+                                                                            egui::RichText::new(
+                                                                                "🔗 deleted item",
+                                                                            )
+                                                                            .weak()
+                                                                            .strikethrough(),
+                                                                        );
+                                                                        if ui
+                                                                            .small_button("✖")
+                                                                            .on_hover_text(
+                                                                                "Remove link",
+                                                                            )
+                                                                            .clicked()
+                                                                        {
+                                                                            unlink = Some(n);
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                        });
+                                                    });
+                                                    if let Some(n) = unlink {
+                                                        item.links.remove(n);
+                                                        self.changed = true;
+                                                    }
+                                                }
+                                                // Draw any children indented under their parent,
+                                                // along with the text box for adding another.
+                                                if !item.children.is_empty()
+                                                    || item.new_child.is_some()
+                                                {
+                                                    ui.indent(("children", item.id), |ui| {
+                                                        let mut actions = Vec::new();
+                                                        let mut remove = None;
+                                                        for (child_idx, child) in
+                                                            item.children.iter_mut().enumerate()
+                                                        {
+                                                            let mut done = child.is_done;
+                                                            let resp = rtl::checkbox(
+                                                                ui,
+                                                                &mut done,
+                                                                item_text(ui.style(), child, false),
+                                                            );
+                                                            if resp.changed() {
+                                                                actions.push(Action::SetDone {
+                                                                    item: child.id,
+                                                                    done,
+                                                                });
+                                                                child.set_done(done);
+                                                            }
+                                                            // Sub-items get a smaller menu of their
+                                                            // own, since they can't be nested further.
+                                                            touch::context_menu(
+                                                                ui,
+                                                                &resp,
+                                                                touch,
+                                                                |ui| {
+                                                                    let label =
+                                                                        if child.is_important {
+                                                                            "Unmark as important"
+                                                                        } else {
+                                                                            "Mark as important"
+                                                                        };
+                                                                    if ui.button(label).clicked() {
+                                                                        child.set_important(
+                                                                            !child.is_important,
+                                                                        );
+                                                                        self.changed = true;
+                                                                        ui.close_menu();
+                                                                    }
+                                                                    if ui
+                                                                        .button("Delete sub-item")
+                                                                        .clicked()
+                                                                    {
+                                                                        remove = Some(child_idx);
+                                                                        ui.close_menu();
+                                                                    }
+                                                                },
+                                                            );
+                                                        }
+                                                        if let Some(child_idx) = remove {
+                                                            let child =
+                                                                item.children.remove(child_idx);
+                                                            self.trash.push(trash::Entry::item(
+                                                                &list.name,
+                                                                child.clone(),
+                                                            ));
+                                                            actions.push(Action::DeleteItem {
+                                                                list: list.id,
+                                                                parent: Some(item.id),
+                                                                index: child_idx,
+                                                                item: Box::new(child),
+                                                            });
+                                                        }
+                                                        // Enter adds the sub-item and keeps the box
+                                                        // open for the next one. Escape closes it.
+                                                        if let Some(text) = &mut item.new_child {
+                                                            let resp = ui.add(
+                                                                egui::TextEdit::singleline(text)
+                                                                    .id(egui::Id::new((
+                                                                        "new sub-item",
+                                                                        item.id,
+                                                                    )))
+                                                                    .hint_text("new sub-item"),
+                                                            );
+                                                            if resp.lost_focus() {
+                                                                let (enter, escape) = {
+                                                                    let input = ui.input();
+                                                                    (
+                                                                        input.key_pressed(
+                                                                            egui::Key::Enter,
+                                                                        ),
+                                                                        input.key_pressed(
+                                                                            egui::Key::Escape,
+                                                                        ),
+                                                                    )
+                                                                };
+                                                                let name = std::mem::take(text);
+                                                                let name = name.trim();
+                                                                if !escape && !name.is_empty() {
+                                                                    item.children.push(Item::new(
+                                                                        name.into(),
+                                                                    ));
+                                                                    self.changed = true;
+                                                                }
+                                                                if enter && !name.is_empty() {
+                                                                    resp.request_focus();
+                                                                } else {
+                                                                    item.new_child = None;
+                                                                }
+                                                            }
+                                                        }
+                                                        // The parent is done once all of its
+                                                        // sub-items are, and not before.
+                                                        let follow = item.follow_children();
+                                                        if !actions.is_empty() {
+                                                            actions.extend(follow);
+                                                            self.undo.push(Action::Batch(actions));
+                                                            self.changed = true;
+                                                        } else if follow.is_some() {
+                                                            self.changed = true;
+                                                        }
+                                                    });
+                                                }
+                                                // Draw a context menu if this item is right-clicked
+                                                // (or long-pressed in touch mode).
+                                                touch::context_menu(ui, &resp, touch, |ui| {
+                                                    // With several items selected, the menu is for
+                                                    // all of them at once.
+                                                    if self.selection.len() > 1
+                                                        && self.selection.contains(item.id)
+                                                    {
+                                                        ui.label(
+                                                            egui::RichText::new(format!(
+                                                                "{} selected",
+                                                                self.selection.len()
+                                                            ))
+                                                            .weak(),
+                                                        );
+                                                        for (label, action) in [
+                                                            ("Mark done", Bulk::Done),
+                                                            ("Mark important", Bulk::Important),
+                                                            ("Delete selected", Bulk::Delete),
+                                                        ] {
+                                                            if ui.button(label).clicked() {
+                                                                bulk = Some(action);
+                                                                self.changed = true;
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                        ui.menu_button("Move selected to", |ui| {
+                                                            for (target, name) in
+                                                                list_names.iter().enumerate()
+                                                            {
+                                                                if ui.button(name).clicked() {
+                                                                    bulk =
+                                                                        Some(Bulk::MoveTo(target));
+                                                                    self.changed = true;
+                                                                    ui.close_menu();
+                                                                }
+                                                            }
+                                                        });
+                                                        return;
+                                                    }
+                                                    // Pinned items stay at the top of the list.
+                                                    if ui
+                                                        .checkbox(&mut item.pinned, "Pin")
+                                                        .clicked()
+                                                    {
+                                                        self.changed = true;
+                                                        ui.close_menu();
+                                                    }
+                                                    // A submenu for setting how soon the item needs
+                                                    // doing. High is the same as important.
+                                                    ui.menu_button("Priority", |ui| {
+                                                        let options = std::iter::once(None)
+                                                            .chain(Priority::ALL.map(Some));
+                                                        for priority in options {
+                                                            let label = priority
+                                                                .map_or("None", Priority::label);
+                                                            if ui
+                                                                .radio(
+                                                                    item.priority() == priority,
+                                                                    label,
+                                                                )
+                                                                .clicked()
+                                                            {
+                                                                item.set_priority(priority);
+                                                                self.changed = true;
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                    });
+                                                    // A submenu for guessing how long the item will take.
+                                                    ui.menu_button("Effort", |ui| {
+                                                        let options = std::iter::once(None)
+                                                            .chain(effort::PRESETS.map(Some));
+                                                        for effort in options {
+                                                            let label = effort.map_or(
+                                                                "None".to_string(),
+                                                                effort::label,
+                                                            );
+                                                            if ui
+                                                                .radio(item.effort == effort, label)
+                                                                .clicked()
+                                                            {
+                                                                item.effort = effort;
+                                                                self.changed = true;
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                    });
+                                                    // A submenu for picking where the item can be done.
+                                                    ui.menu_button("Context", |ui| {
+                                                        if context::menu(ui, item, &contexts) {
+                                                            self.changed = true;
+                                                        }
+                                                    });
+                                                    // A submenu for adding and removing tags.
+                                                    ui.menu_button("Tags", |ui| {
+                                                        if tag::menu(ui, item, &tags_in_use) {
+                                                            self.changed = true;
+                                                        }
+                                                    });
+                                                    // A submenu for picking an emoji to go before the name.
+                                                    ui.menu_button("Icon", |ui| {
+                                                        if icon::picker(ui, &mut item.icon) {
+                                                            self.changed = true;
+                                                        }
+                                                    });
+                                                    // A submenu for tagging the item with a color.
+                                                    ui.menu_button("Color", |ui| {
+                                                        let options = std::iter::once(None)
+                                                            .chain(Tag::ALL.map(Some));
+                                                        for tag in options {
+                                                            let label =
+                                                                tag.map_or("None", Tag::label);
+                                                            if ui
+                                                                .radio(item.tag == tag, label)
+                                                                .clicked()
+                                                            {
+                                                                item.tag = tag;
+                                                                self.changed = true;
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                    });
+                                                    // A submenu for linking this item to another one.
+                                                    ui.menu_button("Link to", |ui| {
+                                                        for (l, items) in
+                                                            items_by_list.iter().enumerate()
+                                                        {
+                                                            ui.menu_button(&list_names[l], |ui| {
+                                                                for (id, name) in items {
+                                                                    if *id != item.id
+                                                                        && !item.links.contains(id)
+                                                                        && ui.button(name).clicked()
+                                                                    {
+                                                                        item.links.push(*id);
+                                                                        self.changed = true;
+                                                                        ui.close_menu();
+                                                                    }
+                                                                }
+                                                            });
+                                                        }
+                                                    });
+                                                    // A submenu for picking an item that has to be
+                                                    // done before this one.
+                                                    ui.menu_button("Blocked by", |ui| {
+                                                        if item.blocked_by.is_some()
+                                                            && ui.button("Nothing").clicked()
+                                                        {
+                                                            item.blocked_by = None;
+                                                            self.changed = true;
+                                                            ui.close_menu();
+                                                        }
+                                                        for (l, items) in
+                                                            items_by_list.iter().enumerate()
+                                                        {
+                                                            ui.menu_button(&list_names[l], |ui| {
+                                                                for (id, name) in items {
+                                                                    if *id == item.id {
+                                                                        continue;
+                                                                    }
+                                                                    let picked = item.blocked_by
+                                                                        == Some(*id);
+                                                                    if ui
+                                                                        .radio(picked, name)
+                                                                        .clicked()
+                                                                    {
+                                                                        item.blocked_by = Some(*id);
+                                                                        self.changed = true;
+                                                                        ui.close_menu();
+                                                                    }
+                                                                }
+                                                            });
+                                                        }
+                                                    });
+                                                    // A button for breaking the item down into steps.
+                                                    if ui.button("Add sub-item").clicked() {
+                                                        item.new_child = Some(String::new());
+                                                        ui.memory().request_focus(egui::Id::new((
+                                                            "new sub-item",
+                                                            item.id,
+                                                        )));
+                                                        ui.close_menu();
+                                                    }
+                                                    // A button for writing notes about the item.
+                                                    if ui.button("Edit notes…").clicked() {
+                                                        item.editing_notes = true;
+                                                        item.notes_open = true;
+                                                        item.begin_editing = true;
+                                                        ui.close_menu();
+                                                    }
+                                                    // Buttons for setting or clearing the due date.
+                                                    if ui.button("Set due date…").clicked() {
+                                                        self.due_editor = Some(DueEditor::new(
+                                                            list_idx,
+                                                            idx,
+                                                            item.due,
+                                                            resp.rect.left_bottom(),
+                                                        ));
+                                                        ui.close_menu();
+                                                    }
+                                                    if item.due.is_some()
+                                                        && ui.button("Clear due date").clicked()
+                                                    {
+                                                        item.set_due(None);
+                                                        self.changed = true;
+                                                        ui.close_menu();
+                                                    }
+                                                    // A submenu for making the item recur.
+                                                    ui.menu_button("Repeat", |ui| {
+                                                        let options = std::iter::once(None)
+                                                            .chain(Recurrence::ALL.map(Some));
+                                                        for recurrence in options {
+                                                            let label = recurrence
+                                                                .map_or("Never", Recurrence::label);
+                                                            if ui
+                                                                .radio(
+                                                                    item.recurrence == recurrence,
+                                                                    label,
+                                                                )
+                                                                .clicked()
+                                                            {
+                                                                item.recurrence = recurrence;
+                                                                self.changed = true;
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                    });
+                                                    // A submenu for hiding the item for a while.
+                                                    ui.menu_button("Snooze", |ui| {
+                                                        for preset in snooze::Preset::ALL {
+                                                            if ui.button(preset.label()).clicked() {
+                                                                item.snoozed_until =
+                                                                    Some(preset.until(clock));
+                                                                self.changed = true;
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                        ui.menu_button("Custom…", |ui| {
+                                                            let text_id =
+                                                                egui::Id::new(("snooze", item.id));
+                                                            let mut text: String = ui
+                                                                .data()
+                                                                .get_temp(text_id)
+                                                                .unwrap_or_default();
+                                                            let parsed =
+                                                                snooze::parse(&text, clock);
+                                                            let resp = ui.add(
+                                                                egui::TextEdit::singleline(
+                                                                    &mut text,
+                                                                )
+                                                                .hint_text("YYYY-MM-DD HH:MM")
+                                                                .desired_width(140.0),
+                                                            );
+                                                            let enter = resp.lost_focus()
+                                                                && ui
+                                                                    .input()
+                                                                    .key_pressed(egui::Key::Enter);
+                                                            if let Some(until) = parsed {
+                                                                ui.label(
+                                                                    egui::RichText::new(
+                                                                        snooze::label(until, clock),
+                                                                    )
+                                                                    .weak(),
+                                                                );
+                                                            }
+                                                            if ui
+                                                                .add_enabled(
+                                                                    parsed.is_some(),
+                                                                    egui::Button::new("Snooze"),
+                                                                )
+                                                                .clicked()
+                                                                || (enter && parsed.is_some())
+                                                            {
+                                                                item.snoozed_until = parsed;
+                                                                self.changed = true;
+                                                                text.clear();
+                                                                ui.close_menu();
+                                                            }
+                                                            ui.data().insert_temp(text_id, text);
+                                                        });
+                                                    });
+                                                    if item.snoozed_until.is_some()
+                                                        && ui.button("Unsnooze").clicked()
+                                                    {
+                                                        item.snoozed_until = None;
+                                                        self.changed = true;
+                                                        ui.close_menu();
+                                                    }
+                                                    // A button for showing the item's details.
+                                                    if ui.button("Details").clicked() {
+                                                        self.details = Some((list_idx, idx));
+                                                        ui.close_menu();
+                                                    }
+                                                    // A submenu with the latest changes to the item.
+                                                    // The rest are in the details.
+                                                    ui.menu_button("History", |ui| {
+                                                        if item.history.is_empty() {
+                                                            ui.label(
+                                                                egui::RichText::new("nothing yet")
+                                                                    .weak(),
+                                                            );
+                                                        }
+                                                        for entry in
+                                                            item.history.iter().rev().take(5)
+                                                        {
+                                                            ui.label(format!(
+                                                                "{} · {}",
+                                                                entry.time_string(),
+                                                                entry.event
+                                                            ));
+                                                        }
+                                                        if ui.button("Show all").clicked() {
+                                                            self.details = Some((list_idx, idx));
+                                                            ui.close_menu();
+                                                        }
+                                                    });
+                                                    // A submenu for moving the item to another list.
+                                                    if list_names.len() > 1 {
+                                                        ui.menu_button("Move to", |ui| {
+                                                            for (target, name) in
+                                                                list_names.iter().enumerate()
+                                                            {
+                                                                if target != list_idx
+                                                                    && ui.button(name).clicked()
+                                                                {
+                                                                    convert =
+                                                                        Some(Convert::MoveItem {
+                                                                            list: list.id,
+                                                                            item: item.id,
+                                                                            target: list_ids
+                                                                                [target],
+                                                                        });
+                                                                    self.changed = true;
+                                                                    ui.close_menu();
+                                                                }
+                                                            }
+                                                        });
+                                                    }
+                                                    // A button for saving the item to make more like it.
+                                                    if ui.button("Save as template").clicked() {
+                                                        self.templates.add_item(item);
+                                                        ui.close_menu();
+                                                    }
+                                                    // A button for turning the item into a list of its own.
+                                                    if ui.button("Convert to list").clicked() {
+                                                        convert = Some(Convert::ItemToList {
+                                                            list: list.id,
+                                                            item: item.id,
+                                                        });
+                                                        self.changed = true;
+                                                        ui.close_menu();
+                                                    }
+                                                    // A button for moving a finished item down into
+                                                    // the archive.
+                                                    if item.is_done
+                                                        && ui.button("Archive").clicked()
+                                                    {
+                                                        archive = Some(idx);
+                                                        self.changed = true;
+                                                        ui.close_menu();
+                                                    }
+                                                    // A button for deleting the item. Items with
+                                                    // sub-items ask first.
+                                                    if ui.button("Delete item").clicked() {
+                                                        if item.children.is_empty() {
+                                                            delete = Some(idx);
+                                                            self.changed = true;
+                                                        } else {
+                                                            self.pending_delete =
+                                                                Some(PendingDelete::Item(item.id));
+                                                        }
+                                                        ui.close_menu();
+                                                    };
+                                                });
+                                                // Remember how tall the row was, for when it's
+                                                // out of view.
+                                                let height = ui.cursor().top() - row_top;
+                                                ui.data().insert_temp(row_id, height);
                                             }
-                                            if resp.double_clicked() {
-                                                item.editing = true;
-                                                item.begin_editing = true;
+                                            // If an item was marked for deletion, remove it.
+                                            // We don't use swap_remove() here since the order
+                                            // of items might matter to the user.
+                                            if let Some(idx) = delete {
+                                                let item = list.items.remove(idx);
+                                                self.trash.push(trash::Entry::item(
+                                                    &list.name,
+                                                    item.clone(),
+                                                ));
+                                                self.undo.push(Action::DeleteItem {
+                                                    list: list.id,
+                                                    parent: None,
+                                                    index: idx,
+                                                    item: Box::new(item),
+                                                });
+                                                self.details = None;
+                                                self.due_editor = None;
+                                                self.focused = None;
+                                                self.selection.clear();
+                                                list.duplicate = None;
+                                                list.highlight = None;
+                                            } else if let Some(idx) = archive {
+                                                // Archiving an item moves it to the bottom
+                                                // of the archive.
+                                                let mut item = list.items.remove(idx);
+                                                item.history.push(Event::Archived);
+                                                list.archived.push(item);
+                                                self.details = None;
+                                                self.due_editor = None;
+                                                self.focused = None;
+                                                self.selection.clear();
+                                                list.duplicate = None;
+                                                list.highlight = None;
                                             }
-                                            resp
-                                        };
-                                        // Draw a context menu if this item is right-clicked.
-                                        resp.context_menu(|ui| {
-                                            // A check box for marking the item as important.
-                                            if ui
-                                                .checkbox(
-                                                    &mut item.is_important,
-                                                    "Mark as important",
+                                            // Point out any snoozed items, which can be shown
+                                            // for a while by clicking.
+                                            let snoozed = list
+                                                .items
+                                                .iter()
+                                                .filter(|item| item.snoozed_until.is_some())
+                                                .count();
+                                            // And how many completed items are hidden.
+                                            if hide_done {
+                                                let hidden = list
+                                                    .items
+                                                    .iter()
+                                                    .filter(|item| item.hidden(now))
+                                                    .count();
+                                                if hidden > 0 {
+                                                    ui.label(
+                                                        egui::RichText::new(format!(
+                                                            "({hidden} hidden)"
+                                                        ))
+                                                        .weak(),
+                                                    );
+                                                }
+                                            }
+                                            if snoozed == 0 {
+                                                list.show_snoozed = false;
+                                            } else if ui
+                                                .add(
+                                                    egui::Label::new(
+                                                        egui::RichText::new(if list.show_snoozed {
+                                                            "(hide snoozed)".into()
+                                                        } else {
+                                                            format!("({snoozed} snoozed)")
+                                                        })
+                                                        .weak(),
+                                                    )
+                                                    .sense(egui::Sense::click()),
                                                 )
-                                                .changed()
+                                                .clicked()
                                             {
-                                                self.changed = true;
-                                                ui.close_menu();
+                                                list.show_snoozed = !list.show_snoozed;
                                             }
-                                            // A buttom for deleting the item.
-                                            if ui.button("Delete item").clicked() {
-                                                delete = Some(idx);
-                                                self.changed = true;
-                                                ui.close_menu();
-                                            };
-                                        });
-                                    }
-                                    // If an item was marked for deletion, remove it.
-                                    // We don't use swap_remove() here since the order
-                                    // of items might matter to the user.
-                                    if let Some(idx) = delete {
-                                        list.items.remove(idx);
-                                    }
-                                    {
-                                        // A text box for adding a new item to this list.
-                                        let resp =
-                                            egui::TextEdit::singleline(&mut list.new_item_name)
-                                                .hint_text("new item")
+                                            {
+                                                // A text box for adding a new item to this list.
+                                                let resp = egui::TextEdit::singleline(
+                                                    &mut list.new_item_name,
+                                                )
+                                                .hint_text("new item (! for important)")
                                                 .desired_width(ui.available_width())
                                                 .show(ui)
                                                 .response;
+                                                if list.focus_new_item {
+                                                    resp.request_focus();
+                                                    resp.scroll_to_me(Some(egui::Align::Center));
+                                                    list.focus_new_item = false;
+                                                }
+                                                // Pasting several lines adds an item for each.
+                                                if list.new_item_name.contains('\n') {
+                                                    let text =
+                                                        std::mem::take(&mut list.new_item_name);
+                                                    list.add_lines(&text);
+                                                    self.changed = true;
+                                                }
 
-                                        if resp.lost_focus() {
-                                            list.new_item_name = list.new_item_name.trim().into();
+                                                // Typing something else means the
+                                                // question about the duplicate is moot.
+                                                if resp.changed() {
+                                                    list.duplicate = None;
+                                                }
+                                                if resp.lost_focus() {
+                                                    list.new_item_name =
+                                                        list.new_item_name.trim().into();
 
-                                            if ui.input().key_pressed(egui::Key::Enter) {
-                                                list.items
-                                                    .push(Item::new(list.new_item_name.clone()));
-                                                list.new_item_name = String::new();
+                                                    if ui.input().key_pressed(egui::Key::Enter) {
+                                                        let existing = if warn_duplicates {
+                                                            list.find_item(
+                                                                quick_add::parse_important(
+                                                                    &list.new_item_name,
+                                                                )
+                                                                .0,
+                                                            )
+                                                        } else {
+                                                            None
+                                                        };
+                                                        match existing {
+                                                            // It's already on the list, so
+                                                            // just point it out.
+                                                            Some(existing)
+                                                                if !list.items[existing]
+                                                                    .is_done =>
+                                                            {
+                                                                list.highlight =
+                                                                    Some((existing, None));
+                                                                list.new_item_name.clear();
+                                                                ui.ctx().request_repaint();
+                                                            }
+                                                            // It's been done already, so ask
+                                                            // what to do about it.
+                                                            Some(existing) => {
+                                                                list.duplicate = Some(existing);
+                                                            }
+                                                            None => {
+                                                                self.changed |=
+                                                                    list.submit_new_item();
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                            // Ask whether a finished duplicate should be
+                                            // unchecked instead of added again.
+                                            if let Some(existing) = list.duplicate {
+                                                ui.horizontal_wrapped(|ui| {
+                                                    ui.label(
+                                                        egui::RichText::new(
+                                                            "Already on the list and done.",
+                                                        )
+                                                        .weak(),
+                                                    );
+                                                    if ui.button("Uncheck it").clicked() {
+                                                        self.undo.push(Action::SetDone {
+                                                            item: list.items[existing].id,
+                                                            done: false,
+                                                        });
+                                                        list.items[existing].set_done(false);
+                                                        list.highlight = Some((existing, None));
+                                                        list.new_item_name.clear();
+                                                        list.duplicate = None;
+                                                        self.changed = true;
+                                                    }
+                                                    if ui.button("Add anyway").clicked() {
+                                                        self.changed |= list.submit_new_item();
+                                                    }
+                                                });
+                                            }
+                                            // Archived items are kept out of the way at the bottom.
+                                            if !list.archived.is_empty() {
+                                                let mut restore = None;
+                                                let mut purge = None;
+                                                egui::CollapsingHeader::new(format!(
+                                                    "Archived ({})",
+                                                    list.archived.len()
+                                                ))
+                                                .id_source(("archived", list.id))
+                                                .default_open(false)
+                                                .show(ui, |ui| {
+                                                    for (idx, item) in
+                                                        list.archived.iter().enumerate()
+                                                    {
+                                                        let resp = ui.add(
+                                                            egui::Label::new(
+                                                                egui::RichText::new(&item.name)
+                                                                    .strikethrough()
+                                                                    .weak(),
+                                                            )
+                                                            .sense(egui::Sense::click()),
+                                                        );
+                                                        touch::context_menu(
+                                                            ui,
+                                                            &resp,
+                                                            touch,
+                                                            |ui| {
+                                                                if ui.button("Restore").clicked() {
+                                                                    restore = Some(idx);
+                                                                    ui.close_menu();
+                                                                }
+                                                                if ui
+                                                                    .button("Delete permanently")
+                                                                    .clicked()
+                                                                {
+                                                                    purge = Some(idx);
+                                                                    ui.close_menu();
+                                                                }
+                                                            },
+                                                        );
+                                                    }
+                                                });
+                                                if let Some(idx) = restore {
+                                                    let mut item = list.archived.remove(idx);
+                                                    item.history.push(Event::Unarchived);
+                                                    list.items.push(item);
+                                                    self.changed = true;
+                                                }
+                                                if let Some(idx) = purge {
+                                                    list.archived.remove(idx);
+                                                    self.changed = true;
+                                                }
+                                            }
+                                        });
+                                    // Clicking the name toggles the list, just like the arrow.
+                                    if header.inner.clicked() {
+                                        if let Some(mut state) = CollapsingState::load(ui.ctx(), id)
+                                        {
+                                            state.toggle(ui);
+                                            state.store(ui.ctx());
+                                        }
+                                    }
+                                    if idx == 0 {
+                                        targets.header = Some(header.response.rect);
+                                    }
+                                    // Underline the header with a thin progress bar.
+                                    if total > 0 {
+                                        let rect = header.response.rect;
+                                        let track = egui::Rect::from_min_max(
+                                            egui::pos2(rect.left(), rect.bottom()),
+                                            egui::pos2(rect.right(), rect.bottom() + 2.0),
+                                        );
+                                        let width = track.width() * done as f32 / total as f32;
+                                        let filled = if rtl {
+                                            egui::Rect::from_x_y_ranges(
+                                                (track.right() - width)..=track.right(),
+                                                track.y_range(),
+                                            )
+                                        } else {
+                                            egui::Rect::from_x_y_ranges(
+                                                track.left()..=(track.left() + width),
+                                                track.y_range(),
+                                            )
+                                        };
+                                        let painter = ui.painter();
+                                        painter.rect_filled(
+                                            track,
+                                            1.0,
+                                            ui.visuals().faint_bg_color,
+                                        );
+                                        painter.rect_filled(
+                                            filled,
+                                            1.0,
+                                            ui.visuals().selection.bg_fill,
+                                        );
+                                    }
+                                    // Paint the list's color in a strip just outside the
+                                    // start of the header and its items, and faintly behind
+                                    // the items themselves.
+                                    if let Some(color) = list.color {
+                                        let mut rect = header.response.rect;
+                                        if let Some(body) = &body {
+                                            ui.painter().set(
+                                                wash,
+                                                epaint::Shape::rect_filled(
+                                                    body.response.rect,
+                                                    4.0,
+                                                    color::wash(color),
+                                                ),
+                                            );
+                                            rect = rect.union(body.response.rect);
+                                        }
+                                        let x = if rtl {
+                                            rect.right()..=(rect.right() + 3.0)
+                                        } else {
+                                            (rect.left() - 3.0)..=rect.left()
+                                        };
+                                        ui.painter().rect_filled(
+                                            egui::Rect::from_x_y_ranges(x, rect.y_range()),
+                                            1.0,
+                                            color::strip(color),
+                                        );
+                                    }
+                                    list_rects.push(match &body {
+                                        Some(body) => {
+                                            header.response.rect.union(body.response.rect)
+                                        }
+                                        None => header.response.rect,
+                                    });
+                                    // The row itself only senses hovering, so give the empty
+                                    // space after the name something to click on too. The
+                                    // arrow and the name get their clicks first, so all three
+                                    // are combined to open the menu from anywhere on the row.
+                                    let row = ui.interact(
+                                        header.response.rect,
+                                        id.with("header row"),
+                                        egui::Sense::click(),
+                                    );
+                                    let resp = arrow.union(header.inner).union(row);
+                                    // Draw a context menu if this list header is right-clicked
+                                    // (or long-pressed in touch mode).
+                                    touch::context_menu(ui, &resp, touch, |ui| {
+                                        // A submenu for renaming the list. Text boxes
+                                        // misbehave right in a header's context menu, but
+                                        // not in a submenu. What's been typed is kept
+                                        // between frames while the menu is open.
+                                        ui.menu_button("Rename", |ui| {
+                                            let id = egui::Id::new(("rename list", list.id));
+                                            let mut text = ui
+                                                .data()
+                                                .get_temp::<String>(id)
+                                                .unwrap_or_else(|| list.name.clone());
+                                            let resp = egui::TextEdit::singleline(&mut text)
+                                                .desired_width(120.0)
+                                                .show(ui)
+                                                .response;
+                                            if resp.lost_focus()
+                                                && ui.input().key_pressed(egui::Key::Enter)
+                                            {
+                                                ui.data().remove::<String>(id);
+                                                rename = Some((list.id, text));
+                                                ui.close_menu();
+                                            } else {
+                                                ui.data().insert_temp(id, text);
+                                            }
+                                        });
+                                        // Starred lists are kept above the rest.
+                                        if ui.checkbox(&mut list.starred, "Star").clicked() {
+                                            self.changed = true;
+                                            ui.close_menu();
+                                        }
+                                        // A submenu for grouping this list with others.
+                                        ui.menu_button("Folder", |ui| {
+                                            if folder::menu(ui, list, &folders) {
                                                 self.changed = true;
                                             }
+                                        });
+                                        // A button for saving the list to make more like it.
+                                        if ui.button("Save as template").clicked() {
+                                            self.templates.add_list(list);
+                                            ui.close_menu();
                                         }
+                                        // A submenu for adding an item made from a template.
+                                        ui.menu_button("New from template", |ui| {
+                                            if let Some(item) = self.templates.item_menu(ui) {
+                                                list.items.push(item);
+                                                self.changed = true;
+                                            }
+                                        });
+                                        // A button for copying this list as a Markdown task list.
+                                        if ui.button("Export as Markdown").clicked() {
+                                            ui.output().copied_text =
+                                                markdown::export(std::slice::from_ref(list));
+                                            ui.close_menu();
+                                        }
+                                        // An option for keeping completed items at the bottom.
+                                        // Turning it off leaves everything where it is.
+                                        if ui
+                                            .checkbox(
+                                                &mut list.sort_done_last,
+                                                "Sort done items last",
+                                            )
+                                            .changed()
+                                        {
+                                            self.changed = true;
+                                            ui.ctx().request_repaint();
+                                        }
+                                        // A button for ordering the items by when they were done.
+                                        if ui
+                                            .add_enabled(
+                                                list.items.iter().any(|item| item.is_done),
+                                                egui::Button::new("Sort by completion time"),
+                                            )
+                                            .clicked()
+                                        {
+                                            list.sort_by_completion();
+                                            self.details = None;
+                                            self.due_editor = None;
+                                            self.focused = None;
+                                            self.changed = true;
+                                            ui.close_menu();
+                                        }
+                                        // A submenu for showing or hiding completed items in
+                                        // this list, whatever the eye in the title bar says.
+                                        ui.menu_button("Completed items", |ui| {
+                                            for (label, show) in [
+                                                ("Follow global", None),
+                                                ("Always show completed", Some(true)),
+                                                ("Always hide completed", Some(false)),
+                                            ] {
+                                                if ui
+                                                    .radio(list.show_completed == show, label)
+                                                    .clicked()
+                                                {
+                                                    list.show_completed = show;
+                                                    self.changed = true;
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                        });
+                                        // A submenu for unchecking everything on a schedule.
+                                        ui.menu_button("Reset checkboxes", |ui| {
+                                            let options = std::iter::once(None)
+                                                .chain(Recurrence::ALL.map(Some));
+                                            for reset in options {
+                                                let label =
+                                                    reset.map_or("Never", Recurrence::label);
+                                                if ui.radio(list.reset == reset, label).clicked() {
+                                                    // Starting now, so nothing's reset right away.
+                                                    if list.reset.is_none() {
+                                                        list.last_reset = Some(Local::now());
+                                                    }
+                                                    list.reset = reset;
+                                                    self.changed = true;
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                            if list.reset.is_some() {
+                                                ui.separator();
+                                                ui.horizontal(|ui| {
+                                                    ui.label("At");
+                                                    let hour = ui.add(
+                                                        egui::DragValue::new(&mut list.reset_hour)
+                                                            .clamp_range(0..=23)
+                                                            .suffix(":00"),
+                                                    );
+                                                    if hour.changed() {
+                                                        self.changed = true;
+                                                    }
+                                                });
+                                            }
+                                        });
+                                        // A submenu for giving the list a color.
+                                        ui.menu_button("Set color", |ui| {
+                                            let options =
+                                                std::iter::once(("None", None))
+                                                    .chain(color::PALETTE.map(|(label, color)| {
+                                                        (label, Some(color))
+                                                    }));
+                                            for (label, color) in options {
+                                                if ui.radio(list.color == color, label).clicked() {
+                                                    list.color = color;
+                                                    self.changed = true;
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                        });
+                                        // Buttons for checking or unchecking everything at once,
+                                        // like for starting a checklist over.
+                                        for (label, done) in
+                                            [("Check all", true), ("Uncheck all", false)]
+                                        {
+                                            if ui
+                                                .add_enabled(
+                                                    list.items
+                                                        .iter()
+                                                        .any(|item| item.is_done != done),
+                                                    egui::Button::new(label),
+                                                )
+                                                .clicked()
+                                            {
+                                                let mut actions = Vec::new();
+                                                for item in &mut list.items {
+                                                    if item.is_done != done {
+                                                        item.set_done(done);
+                                                        actions.push(Action::SetDone {
+                                                            item: item.id,
+                                                            done,
+                                                        });
+                                                    }
+                                                }
+                                                self.undo.push(Action::Batch(actions));
+                                                self.changed = true;
+                                                ui.close_menu();
+                                            }
+                                        }
+                                        // A button for putting away everything that's done.
+                                        if ui
+                                            .add_enabled(
+                                                list.items.iter().any(|item| item.is_done),
+                                                egui::Button::new("Archive completed"),
+                                            )
+                                            .clicked()
+                                        {
+                                            list.archive_completed();
+                                            self.details = None;
+                                            self.due_editor = None;
+                                            self.changed = true;
+                                            ui.close_menu();
+                                        }
+                                        // A button for deleting everything that's done.
+                                        // Deleting more than a few asks first.
+                                        let completed =
+                                            list.items.iter().filter(|item| item.is_done).count();
+                                        if ui
+                                            .add_enabled(
+                                                completed > 0,
+                                                egui::Button::new("Delete completed items"),
+                                            )
+                                            .clicked()
+                                        {
+                                            if completed > 3 {
+                                                self.pending_delete =
+                                                    Some(PendingDelete::Completed(list.id));
+                                            } else {
+                                                convert = Some(Convert::DeleteCompleted {
+                                                    list: list.id,
+                                                });
+                                                self.changed = true;
+                                            }
+                                            ui.close_menu();
+                                        }
+                                        // A submenu for turning this list into an item in another list.
+                                        if list_names.len() > 1 {
+                                            ui.menu_button("Convert to item in", |ui| {
+                                                for (target, name) in list_names.iter().enumerate()
+                                                {
+                                                    if target != idx && ui.button(name).clicked() {
+                                                        convert = Some(Convert::ListToItem {
+                                                            list: list.id,
+                                                            target: list_ids[target],
+                                                        });
+                                                        self.changed = true;
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                        }
+                                        // Buttons for moving this list up or down, past
+                                        // lists in the same folder and just as starred.
+                                        let can_move_up =
+                                            idx > 0 && list_groups[idx - 1] == list_groups[idx];
+                                        let can_move_down = idx + 1 < len
+                                            && list_groups[idx + 1] == list_groups[idx];
+                                        if ui
+                                            .add_enabled(can_move_up, egui::Button::new("Move up"))
+                                            .clicked()
+                                        {
+                                            reorder = Some((idx, idx - 1));
+                                            self.changed = true;
+                                            ui.close_menu();
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                can_move_down,
+                                                egui::Button::new("Move down"),
+                                            )
+                                            .clicked()
+                                        {
+                                            reorder = Some((idx, idx + 1));
+                                            self.changed = true;
+                                            ui.close_menu();
+                                        }
+                                        // A button for copying this list and everything in it.
+                                        if ui.button("Duplicate list").clicked() {
+                                            convert =
+                                                Some(Convert::DuplicateList { list: list.id });
+                                            self.changed = true;
+                                            ui.close_menu();
+                                        }
+                                        // A button for deleting this list.
+                                        // Lists with items in them ask first.
+                                        if ui.button("Delete list").clicked() {
+                                            if list.items.is_empty() {
+                                                delete = Some(idx);
+                                                self.changed = true;
+                                            } else {
+                                                self.pending_delete =
+                                                    Some(PendingDelete::List(list.id));
+                                            }
+                                            ui.close_menu();
+                                        };
+                                    });
+                                    // Place some space between each list for readability.
+                                    if idx < len - 1 {
+                                        ui.allocate_space(egui::vec2(0.0, 5.0));
                                     }
-                                })
-                                .header_response;
-                            // Draw a context menu if this list header is right-clicked.
-                            resp.context_menu(|ui| {
-                                // A text box for retroactively editing the list name.
-                                // TODO Context menus containing text boxes have
-                                // broken behavior when sourced from a
-                                // CollapsingHeader. Why? I have no idea.
-                                // if ui.text_edit_singleline(&mut list.name).lost_focus() {
-                                //     self.changed = true;
-                                //     ui.close_menu();
-                                // }
-                                // A button for deleting this list.
-                                if ui.button("Delete list").clicked() {
-                                    delete = Some(idx);
+                                }
+                                // The trash goes below everything else.
+                                if !searching
+                                    && !mini
+                                    && trash::show(ui, &mut self.trash, &mut self.lists)
+                                {
                                     self.changed = true;
-                                    ui.close_menu();
-                                };
-                            });
-                            // Place some space between each list for readability.
-                            if idx < len - 1 {
-                                ui.allocate_space(egui::vec2(0.0, 5.0));
-                            }
-                        }
+                                }
+                            },
+                        );
                     });
+                // Finish off a Shift+click selection before anything moves around.
+                self.selection.finish(&self.lists, &visible);
                 // If a list was marked for deletion, remove it.
-                // We can use swap_remove() here to save a couple CPU cycles,
-                // as the order of entire lists doesn't really matter(?)
+                // We don't use swap_remove() here since the order of lists is up
+                // to the user.
                 if let Some(k) = delete {
-                    self.lists.swap_remove(k);
+                    let list = self.lists.remove(k);
+                    list_rects.remove(k);
+                    self.trash.push(trash::Entry::list(list.clone()));
+                    self.undo.push(Action::DeleteList { index: k, list });
+                }
+                // Lists are moved after drawing for the same reason.
+                if let Some((a, b)) = reorder {
+                    self.lists.swap(a, b);
+                    list_rects.swap(a, b);
+                }
+                // Starred lists go above the rest, and lists in the same folder are
+                // kept together.
+                let arranged = folder::arrange(&mut self.lists);
+                if arranged {
+                    list_rects.clear();
+                }
+                // Anything that moves lists or items around also moves whatever the
+                // details window was pointing at.
+                if delete.is_some() || convert.is_some() || reorder.is_some() || arranged {
+                    self.details = None;
+                    self.due_editor = None;
+                    self.focused = None;
+                }
+                // Lists are renamed after drawing, once their names can be checked
+                // against the others.
+                if let Some((id, name)) = rename {
+                    if let Some(idx) = self.lists.iter().position(|list| list.id == id) {
+                        match rename_list(&mut self.lists, idx, &name) {
+                            Ok(renamed) => self.changed |= renamed,
+                            Err(err) => self.notice = Some(err),
+                        }
+                    }
+                }
+                // Items and lists are converted after drawing for the same reason.
+                if let Some(convert) = convert {
+                    convert.apply(&mut self.lists, &mut self.undo, &mut self.trash);
+                    list_rects.clear();
+                }
+                // Selected items are dealt with after drawing for the same reason.
+                if let Some(bulk) = bulk {
+                    bulk.apply(
+                        &mut self.lists,
+                        &self.selection.take(),
+                        &mut self.undo,
+                        &mut self.trash,
+                    );
+                    self.details = None;
+                    self.due_editor = None;
+                    self.focused = None;
                 }
             });
-        if self.changed {
-            // Draw new frames as long as there are unsaved changes so that there's
-            // no risk of leaving them unsaved.
+
+        // Ctrl+H opens Find & Replace.
+        let find_pressed = {
+            let input = ctx.input();
+            input.modifiers.command && input.key_pressed(egui::Key::H)
+        };
+        if find_pressed {
+            self.find_replace.open = true;
+        }
+        if self.find_replace.show(ctx, &mut self.lists) {
+            self.changed = true;
+        }
+        if let Some(jump) = self.find_replace.jump.take() {
+            self.jump = Some(jump);
             ctx.request_repaint();
-            let elapsed = self.last_save.elapsed().as_secs();
-            // Only save if at least 3 seconds have passed since the last save.
-            if elapsed >= 3 {
-                let lists_copy = self.lists.clone();
-                // Save in another thread to keep the UI going.
-                thread::spawn(move || {
-                    let json = serde_json::to_string(&lists_copy).expect("Failed to serialize");
-                    let mut path = home_dir().expect("Failed to find home directory");
-                    path.push(".todoish");
-                    fs::write(path, json).expect("Failed to write to disk");
+        }
+
+        // Ctrl+K (or Ctrl+P) opens the palette for jumping to any list or item.
+        let palette_pressed = {
+            let input = ctx.input();
+            input.modifiers.command
+                && (input.key_pressed(egui::Key::K) || input.key_pressed(egui::Key::P))
+        };
+        if palette_pressed {
+            self.palette.open(ctx);
+        }
+        match self.palette.show(ctx, &self.lists) {
+            Some(palette::Outcome::Jump(palette::Target::Item(list, item))) => {
+                self.jump = Some((list, item));
+                self.focused = Some((list, item));
+                self.stats_open = false;
+                ctx.request_repaint();
+            }
+            Some(palette::Outcome::Jump(palette::Target::List(list))) => {
+                // Going to a list opens it, ready to add something.
+                self.lists[list].focus_new_item = true;
+                self.search.clear();
+                self.stats_open = false;
+                ctx.request_repaint();
+            }
+            Some(palette::Outcome::Toggle(list, item)) => {
+                let item = &mut self.lists[list].items[item];
+                let done = !item.is_effectively_done();
+                self.undo.push(item.set_done_all(done));
+                item.checked_at = done.then_some(now);
+                self.changed = true;
+            }
+            None => {}
+        }
+
+        // Manage items with the keyboard.
+        if keys.up || keys.down {
+            self.focused = keyboard::step(&visible, self.focused, keys.down);
+            ctx.request_repaint();
+        }
+        if keys.escape {
+            self.focused = None;
+            self.selection.clear();
+        }
+        // Only act on the focused item if it's still showing, and still there.
+        let focused = self.focused.filter(|&(list, idx)| {
+            visible.contains(&(list, idx))
+                && self
+                    .lists
+                    .get(list)
+                    .is_some_and(|list| idx < list.items.len())
+        });
+        if let Some((list, idx)) = focused {
+            let item = &mut self.lists[list].items[idx];
+            if keys.toggle {
+                let done = !item.is_effectively_done();
+                self.undo.push(item.set_done_all(done));
+                item.checked_at = done.then_some(now);
+                self.changed = true;
+                // The list might need sorting next frame.
+                ctx.request_repaint();
+            }
+            if keys.edit {
+                item.edit_name = item.name.clone();
+                item.editing = true;
+                item.begin_editing = true;
+                ctx.request_repaint();
+            }
+            if keys.delete && !item.children.is_empty() {
+                // Items with sub-items ask first.
+                self.pending_delete = Some(PendingDelete::Item(item.id));
+            } else if keys.delete {
+                let item = self.lists[list].items.remove(idx);
+                self.trash
+                    .push(trash::Entry::item(&self.lists[list].name, item.clone()));
+                self.undo.push(Action::DeleteItem {
+                    list: self.lists[list].id,
+                    parent: None,
+                    index: idx,
+                    item: Box::new(item),
                 });
-                self.last_save = time::Instant::now();
-                self.changed = false;
+                // Keep the focus in the same spot, unless that was the last item.
+                let len = self.lists[list].items.len();
+                self.focused = (len > 0).then(|| (list, idx.min(len - 1)));
+                self.details = None;
+                self.due_editor = None;
+                self.changed = true;
+            }
+        }
+        if keys.new_item {
+            // Use the list of the focused item, or the first one showing.
+            let list = self
+                .focused
+                .or_else(|| visible.first().copied())
+                .map_or(0, |(list, _)| list);
+            if let Some(list) = self.lists.get_mut(list) {
+                list.focus_new_item = true;
+                ctx.request_repaint();
+            }
+        }
+
+        // Show the details of an item, if one was picked.
+        if let Some((list, item)) = self.details {
+            let mut jump = None;
+            if !details::show(
+                ctx,
+                &mut self.lists,
+                list,
+                item,
+                &mut self.changed,
+                &mut jump,
+            ) {
+                self.details = None;
+            }
+            if jump.is_some() {
+                self.jump = jump;
+                self.stats_open = false;
+                ctx.request_repaint();
+            }
+        }
+
+        if let Some(lists) = self.importer.show(ctx) {
+            self.lists.extend(lists);
+            self.changed = true;
+        }
+
+        // Pick a due date, if one is being set.
+        if let Some(editor) = &mut self.due_editor {
+            match editor.show(ctx) {
+                Some(due::Outcome::Set(date)) => {
+                    let item = self
+                        .lists
+                        .get_mut(editor.list)
+                        .and_then(|list| list.items.get_mut(editor.item));
+                    if let Some(item) = item {
+                        item.set_due(Some(date));
+                        self.changed = true;
+                    }
+                    self.due_editor = None;
+                }
+                Some(due::Outcome::Cancel) => self.due_editor = None,
+                None => {}
+            }
+        }
+
+        // Walk new users through the app.
+        if let Some(step) = self.onboarding {
+            match onboarding::show(ctx, step, &targets) {
+                Some(onboarding::Action::AddSample) => {
+                    self.lists.push(onboarding::welcome_list());
+                    self.changed = true;
+                    self.onboarding = step.next();
+                }
+                Some(onboarding::Action::GoTo(next)) => {
+                    self.onboarding = next;
+                    if next.is_none() {
+                        self.settings.onboarded = true;
+                        settings_changed = true;
+                    }
+                }
+                None => {}
             }
         }
+
+        {
+            // Each line of a text file dropped onto a list becomes a new item in
+            // that list. Files dropped elsewhere go to the inbox. Dragged text
+            // (not in a file) never gets here, since winit doesn't report it.
+            let (hover_pos, hovering, dropped, screen_rect) = {
+                // Don't hold on to the input lock while painting.
+                let input = ctx.input();
+                (
+                    input.pointer.hover_pos(),
+                    !input.raw.hovered_files.is_empty(),
+                    input.raw.dropped_files.clone(),
+                    input.screen_rect(),
+                )
+            };
+            let target =
+                hover_pos.and_then(|pos| list_rects.iter().position(|rect| rect.contains(pos)));
+
+            if hovering {
+                let painter = ctx.layer_painter(egui::LayerId::new(
+                    egui::Order::Foreground,
+                    egui::Id::new("drop overlay"),
+                ));
+                painter.rect_filled(screen_rect, 10.0, egui::Color32::from_black_alpha(160));
+                if let Some(idx) = target {
+                    painter.rect_stroke(list_rects[idx], 4.0, ctx.style().visuals.selection.stroke);
+                }
+                painter.text(
+                    screen_rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "drop to add",
+                    egui::TextStyle::Heading.resolve(&ctx.style()),
+                    egui::Color32::WHITE,
+                );
+            }
+
+            for text in dropped.iter().filter_map(dropped_text) {
+                match target {
+                    Some(idx) => self.lists[idx].add_lines(&text),
+                    None => list_named(&mut self.lists, quick_add::INBOX).add_lines(&text),
+                }
+                self.changed = true;
+            }
+        }
+        // The scale is a setting, so it's saved along with them rather than the lists.
+        if let Some(scale) = new_scale {
+            self.settings.ui_scale = scale;
+            scale::apply(ctx, frame.info().native_pixels_per_point, scale);
+            self.fit_to_scale = true;
+            settings_changed = true;
+        }
+        if settings_changed {
+            if let Err(err) = self.settings.save() {
+                self.notice = Some(format!("Your settings couldn't be saved: {err}"));
+            }
+        }
+
+        // Ask before deleting a list with items in it, a lot of completed items,
+        // or an item with sub-items. This happens last, so that nothing above is
+        // pointing at the wrong list.
+        if let Some(pending) = self.pending_delete {
+            // Where the list is, and the item too if it's just one item.
+            let target = match pending {
+                PendingDelete::List(id) | PendingDelete::Completed(id) => self
+                    .lists
+                    .iter()
+                    .position(|list| list.id == id)
+                    .map(|index| (index, None)),
+                PendingDelete::Item(id) => {
+                    self.lists.iter().enumerate().find_map(|(index, list)| {
+                        let item = list.items.iter().position(|item| item.id == id)?;
+                        Some((index, Some(item)))
+                    })
+                }
+            };
+            match target {
+                Some((index, item_idx)) => {
+                    let list = &self.lists[index];
+                    let plural = |count: usize| if count == 1 { "item" } else { "items" };
+                    let (title, question) = match (pending, item_idx) {
+                        (PendingDelete::Item(_), Some(item_idx)) => {
+                            let item = &list.items[item_idx];
+                            let count = item.children.len();
+                            (
+                                "Delete item?",
+                                format!(
+                                    "Delete \"{}\" and its {count} sub-{}?",
+                                    item.name,
+                                    plural(count)
+                                ),
+                            )
+                        }
+                        (PendingDelete::List(_), _) => (
+                            "Delete list?",
+                            format!(
+                                "Delete \"{}\" and its {} {}?",
+                                list.name,
+                                list.items.len(),
+                                plural(list.items.len())
+                            ),
+                        ),
+                        (PendingDelete::Completed(_), _) => {
+                            let count = list.items.iter().filter(|item| item.is_done).count();
+                            (
+                                "Delete completed items?",
+                                format!(
+                                    "Delete the {count} completed {} in \"{}\"?",
+                                    plural(count),
+                                    list.name
+                                ),
+                            )
+                        }
+                        (PendingDelete::Item(_), None) => {
+                            unreachable!("Items are always found along with their list")
+                        }
+                    };
+                    let mut confirmed = false;
+                    let mut cancelled = ctx.input().key_pressed(egui::Key::Escape);
+                    egui::Window::new(title)
+                        .collapsible(false)
+                        .resizable(false)
+                        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                        .show(ctx, |ui| {
+                            ui.label(question);
+                            ui.horizontal(|ui| {
+                                cancelled |= ui.button("Cancel").clicked();
+                                confirmed = ui.button("Delete").clicked();
+                            });
+                        });
+                    if confirmed {
+                        match (pending, item_idx) {
+                            (PendingDelete::Item(_), Some(item_idx)) => {
+                                let list = &mut self.lists[index];
+                                let item = list.items.remove(item_idx);
+                                self.trash
+                                    .push(trash::Entry::item(&list.name, item.clone()));
+                                self.undo.push(Action::DeleteItem {
+                                    list: list.id,
+                                    parent: None,
+                                    index: item_idx,
+                                    item: Box::new(item),
+                                });
+                                list.duplicate = None;
+                                list.highlight = None;
+                            }
+                            (PendingDelete::List(_), _) => {
+                                let list = self.lists.remove(index);
+                                self.trash.push(trash::Entry::list(list.clone()));
+                                self.undo.push(Action::DeleteList { index, list });
+                            }
+                            (PendingDelete::Completed(_), _) => {
+                                self.lists[index].delete_completed(&mut self.undo, &mut self.trash);
+                            }
+                            (PendingDelete::Item(_), None) => {
+                                unreachable!("Items are always found along with their list")
+                            }
+                        }
+                        self.details = None;
+                        self.due_editor = None;
+                        self.focused = None;
+                        self.selection.clear();
+                        self.changed = true;
+                    }
+                    if confirmed || cancelled {
+                        self.pending_delete = None;
+                    }
+                }
+                // It's already gone, like after an undo.
+                None => self.pending_delete = None,
+            }
+        }
+
+        // Save right away before quitting, rather than leaving it to the save thread.
+        if quit {
+            match self.save_now() {
+                Ok(()) => frame.quit(),
+                Err(err) => self.notice = Some(format!("Couldn't save before quitting: {err}")),
+            }
+        }
+
+        // egui can't tell when the window loses focus, but the pointer leaving it
+        // is just as good a sign that the user is done for now, so anything
+        // waiting to be saved is saved then without waiting for things to settle.
+        let pointer_inside = ctx.input().pointer.has_pointer();
+        let left = self.pointer_inside && !pointer_inside;
+        self.pointer_inside = pointer_inside;
+
+        // Every so often (and before saving), look for changes to the data file
+        // from elsewhere, so they're merged in rather than saved over.
+        if self.changed || self.last_sync.elapsed() >= sync::CHECK_INTERVAL {
+            if self.merge_from_disk() {
+                ctx.request_repaint();
+            }
+            self.last_sync = time::Instant::now();
+        }
+
+        // Hand any changes to the save thread, which waits for things to settle
+        // down before writing them, unless the pointer just left.
+        if self.changed || (left && self.saver.pending()) {
+            let base = self.base.clone();
+            self.stamp();
+            let (lists, trash) = (self.lists.clone(), self.trash.clone());
+            if left {
+                self.saver.save_soon(lists, trash, base, &self.settings);
+            } else {
+                self.saver.save(lists, trash, base, &self.settings);
+            }
+            self.changed = false;
+        }
     }
 }
 
 fn main() {
+    // `todoish --quick-add` shows only the small capture window, and
+    // `todoish --print [--format json]` prints the lists without any window.
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--quick-add") => quick_add::run(args.next().as_deref() == Some("--stdout")),
+        Some("--print") => {
+            let json = match (args.next().as_deref(), args.next().as_deref()) {
+                (None, _) | (Some("--format"), Some("text")) => false,
+                (Some("--format"), Some("json")) => true,
+                _ => {
+                    eprintln!("Usage: todoish --print [--format text|json]");
+                    std::process::exit(2);
+                }
+            };
+            print::run(json);
+        }
+        _ => {}
+    }
+
+    // Mini mode can only keep the window on top from the start. Otherwise, open
+    // the window at the size it was last time, but no smaller than it can be.
+    // Once eframe has seen the window, it puts it back where it was and at the
+    // same size itself, and this is only used the first time.
+    // The window's sizes don't know about the scale, so it's applied to them here.
+    let settings = Settings::load();
+    let mini_mode = settings.mini_mode;
+    let scale = scale::clamp(settings.ui_scale);
+    let window_size = if mini_mode {
+        Some(mini::SIZE)
+    } else {
+        settings
+            .normal_size
+            .map(|[x, y]| egui::vec2(x, y).max(mini::SIZE))
+    };
     let native_options = eframe::NativeOptions {
         // Hide the window header. We don't want to allow the user to accidentally
         // close the window so that their todo lists can always be visible. (a la Tape)
@@ -328,7 +4133,11 @@ fn main() {
         // And of course, since the window isn't decorated, make it transparent
         // So that we're not just stuck with the sharp corners.
         transparent: true,
-        min_window_size: Some(egui::vec2(500.0, 500.0)),
+        // Allow dropping text files onto lists.
+        drag_and_drop_support: true,
+        always_on_top: mini_mode,
+        initial_window_size: window_size.map(|size| size * scale),
+        min_window_size: Some(mini::SIZE * scale),
         ..Default::default()
     };
     eframe::run_native(
@@ -337,3 +4146,192 @@ fn main() {
         Box::new(|cc| Box::new(Todoish::new(cc))),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_are_trimmed() {
+        assert_eq!(
+            finish_rename("Milk", "  Oat milk ", false),
+            Some("Oat milk".into())
+        );
+    }
+
+    #[test]
+    fn renames_that_change_nothing_are_dropped() {
+        assert_eq!(finish_rename("Milk", "Milk", false), None);
+        assert_eq!(finish_rename("Milk", " Milk  ", false), None);
+        assert_eq!(finish_rename("Milk", "   ", false), None);
+        assert_eq!(finish_rename("Milk", "", false), None);
+    }
+
+    #[test]
+    fn cancelled_renames_are_dropped() {
+        assert_eq!(finish_rename("Milk", "Oat milk", true), None);
+    }
+
+    #[test]
+    fn lists_cant_be_renamed_to_a_name_thats_taken() {
+        let mut lists = vec![List::new("Groceries".into()), List::new("Chores".into())];
+        assert!(rename_list(&mut lists, 1, " groceries ").is_err());
+        assert!(rename_list(&mut lists, 1, "GROCERIES").is_err());
+        assert_eq!(lists[1].name, "Chores");
+        // A list can still change the case of its own name.
+        assert_eq!(rename_list(&mut lists, 0, "groceries"), Ok(true));
+        assert_eq!(lists[0].name, "groceries");
+        assert_eq!(rename_list(&mut lists, 1, "  Errands "), Ok(true));
+        assert_eq!(lists[1].name, "Errands");
+        assert_eq!(rename_list(&mut lists, 1, "   "), Ok(false));
+        assert_eq!(rename_list(&mut lists, 1, "Errands"), Ok(false));
+    }
+
+    #[test]
+    fn conversions_find_things_after_lists_move() {
+        let mut groceries = List::new("Groceries".into());
+        let milk = Item::new("Milk".into());
+        let milk_id = milk.id;
+        groceries.items.push(Item::new("Eggs".into()));
+        groceries.items.push(milk);
+        let chores = List::new("Chores".into());
+        let convert = Convert::MoveItem {
+            list: groceries.id,
+            item: milk_id,
+            target: chores.id,
+        };
+        // The lists were swapped, and the eggs deleted, in the same frame.
+        let mut lists = vec![chores, groceries];
+        lists[1].items.remove(0);
+        let mut undo = UndoStack::default();
+        let mut trash = Vec::new();
+        assert!(convert.apply(&mut lists, &mut undo, &mut trash).is_some());
+        assert!(lists[1].items.is_empty());
+        assert_eq!(lists[0].items[0].name, "Milk");
+
+        // Nothing happens if the item's gone.
+        let convert = Convert::ItemToList {
+            list: lists[1].id,
+            item: milk_id,
+        };
+        assert!(convert.apply(&mut lists, &mut undo, &mut trash).is_none());
+        assert_eq!(lists.len(), 2);
+    }
+
+    /// An item with sub-items, done or not in the order given.
+    fn parent(children: &[bool]) -> Item {
+        let mut item = Item::new("Move house".into());
+        for &done in children {
+            let mut child = Item::new("Step".into());
+            child.set_done(done);
+            item.children.push(child);
+        }
+        item
+    }
+
+    #[test]
+    fn items_without_sub_items_are_done_when_checked() {
+        let mut item = Item::new("Milk".into());
+        assert!(!item.is_effectively_done());
+        item.set_done(true);
+        assert!(item.is_effectively_done());
+    }
+
+    #[test]
+    fn items_with_sub_items_are_done_when_all_of_them_are() {
+        assert!(!parent(&[false, false]).is_effectively_done());
+        assert!(!parent(&[true, false]).is_effectively_done());
+        assert!(parent(&[true, true]).is_effectively_done());
+        // Checking the item itself doesn't count while a sub-item is left.
+        let mut item = parent(&[true, false]);
+        item.set_done(true);
+        assert!(!item.is_effectively_done());
+    }
+
+    #[test]
+    fn items_follow_their_sub_items() {
+        let mut item = parent(&[true, false]);
+        assert!(item.follow_children().is_none());
+        item.children[1].set_done(true);
+        assert!(item.follow_children().is_some());
+        assert!(item.is_done);
+        item.children[0].set_done(false);
+        assert!(item.follow_children().is_some());
+        assert!(!item.is_done);
+    }
+
+    #[test]
+    fn progress_counts_items_done_by_their_sub_items() {
+        let mut list = List::new("Groceries".into());
+        list.items.push(parent(&[true, true]));
+        list.items.push(parent(&[true, false]));
+        list.items[1].set_done(true);
+        assert_eq!(list.progress(), (1, 2));
+    }
+
+    /// How `item` is drawn, with nothing in its name but plain text.
+    fn format(item: &Item, blocked: bool) -> egui::TextFormat {
+        let job = item_text(&egui::Style::default(), item, blocked);
+        assert_eq!(job.sections.len(), 1);
+        job.sections[0].format.clone()
+    }
+
+    #[test]
+    fn done_items_are_struck_out_and_dimmed() {
+        let visuals = egui::Style::default().visuals;
+        let mut item = Item::new("Milk".into());
+        item.set_important(true);
+        item.set_done(true);
+        let format = format(&item, false);
+        assert_eq!(format.color, visuals.weak_text_color());
+        assert_eq!(format.strikethrough.width, 1.0);
+        // Being important doesn't matter once it's done.
+        assert_eq!(format.underline, egui::Stroke::none());
+    }
+
+    #[test]
+    fn open_items_are_styled_by_priority() {
+        let visuals = egui::Style::default().visuals;
+        let mut item = Item::new("Milk".into());
+        let plain = format(&item, false);
+        assert_eq!(plain.color, visuals.text_color());
+        assert_eq!(plain.strikethrough, egui::Stroke::none());
+        assert_eq!(plain.underline, egui::Stroke::none());
+        assert!(!plain.italics);
+
+        item.set_important(true);
+        assert_eq!(format(&item, false).underline.width, 1.0);
+        item.set_important(false);
+        item.priority = Some(Priority::Medium);
+        assert!(format(&item, false).italics);
+        item.priority = Some(Priority::Low);
+        assert_eq!(format(&item, false).color, visuals.weak_text_color());
+    }
+
+    #[test]
+    fn blocked_items_are_dimmed() {
+        let visuals = egui::Style::default().visuals;
+        let mut item = Item::new("Milk".into());
+        item.set_important(true);
+        let format = format(&item, true);
+        assert_eq!(format.color, visuals.weak_text_color());
+        assert_eq!(format.underline.width, 1.0);
+    }
+
+    #[test]
+    fn icons_go_before_the_name() {
+        let mut item = Item::new("Milk".into());
+        item.icon = Some("🥛".into());
+        let job = item_text(&egui::Style::default(), &item, false);
+        assert_eq!(job.text, "🥛 Milk");
+    }
+
+    #[test]
+    fn checking_an_item_checks_its_sub_items() {
+        let mut item = parent(&[true, false]);
+        item.set_done_all(true);
+        assert!(item.is_done && item.children.iter().all(|child| child.is_done));
+        item.set_done_all(false);
+        assert!(!item.is_done && item.children.iter().all(|child| !child.is_done));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{Item, List};
+use eframe::egui;
+use std::fmt::Write;
+
+/// The list that imported items go into when there's no heading above them.
+const IMPORTED: &str = "Imported";
+
+/// Write `item` (and its children) as GitHub-style task list lines.
+fn export_item(out: &mut String, item: &Item, depth: usize) {
+    let check = if item.is_done { 'x' } else { ' ' };
+    let indent = "  ".repeat(depth);
+    // Done items aren't emphasized in the app, so they aren't here either.
+    if item.is_important && !item.is_done {
+        writeln!(out, "{indent}- [{check}] **{}**", item.name).unwrap();
+    } else {
+        writeln!(out, "{indent}- [{check}] {}", item.name).unwrap();
+    }
+    for child in &item.children {
+        export_item(out, child, depth + 1);
+    }
+}
+
+/// Write `lists` as Markdown, with a heading for each list followed by its items as a task list.
+pub fn export(lists: &[List]) -> String {
+    let mut out = String::new();
+    for (idx, list) in lists.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "## {}\n", list.name).unwrap();
+        for item in &list.items {
+            export_item(&mut out, item, 0);
+        }
+    }
+    out
+}
+
+/// If `line` is a task list bullet, return how far it's indented, whether it's
+/// checked, and the rest of the line.
+fn parse_task(line: &str) -> Option<(usize, bool, &str)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let rest = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| trimmed.strip_prefix("+ "))?;
+    let (done, rest) = if let Some(rest) = rest.strip_prefix("[ ]") {
+        (false, rest)
+    } else if let Some(rest) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, rest)
+    } else {
+        return None;
+    };
+    Some((indent, done, rest.trim()))
+}
+
+/// Read a pasted (or dropped) line as an item. Bullets ("- ", "* ", "+ ") and
+/// checkboxes ("[ ]", "[x]") are stripped off. Returns the name and whether or
+/// not it was checked, or None if there's nothing left.
+pub fn parse_line(line: &str) -> Option<(&str, bool)> {
+    let mut line = line.trim();
+    // A bullet on its own is an empty item, not one named "-".
+    let bullet = line
+        .strip_prefix(['-', '*', '+'])
+        .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace));
+    if let Some(rest) = bullet {
+        line = rest.trim_start();
+    }
+    let (done, rest) = if let Some(rest) = line.strip_prefix("[ ]") {
+        (false, rest)
+    } else if let Some(rest) = line
+        .strip_prefix("[x]")
+        .or_else(|| line.strip_prefix("[X]"))
+    {
+        (true, rest)
+    } else {
+        (false, line)
+    };
+    let name = rest.trim();
+    (!name.is_empty()).then_some((name, done))
+}
+
+/// Split a pasted block of lines into the name of the list they go in, if the
+/// first line is one like "Groceries:", and the lines for the items.
+pub fn split_list(text: &str) -> (Option<&str>, &str) {
+    let text = text.trim();
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+    match first.trim().strip_suffix(':').map(str::trim) {
+        Some(name) if !name.is_empty() => (Some(name), rest),
+        _ => (None, text),
+    }
+}
+
+/// Read lists out of Markdown. Headings start new lists, and task list bullets
+/// become items (or children, if they're indented). Everything else is ignored.
+pub fn import(text: &str) -> Vec<List> {
+    let mut lists: Vec<List> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            let name = trimmed.trim_start_matches('#').trim();
+            if !name.is_empty() {
+                lists.push(List::new(name.into()));
+            }
+            continue;
+        }
+
+        let (indent, done, name) = match parse_task(line) {
+            Some(task) => task,
+            None => continue,
+        };
+        // Bold items are the important ones.
+        let (important, name) = match name.strip_prefix("**").and_then(|n| n.strip_suffix("**")) {
+            Some(name) => (true, name.trim()),
+            None => (false, name),
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let mut item = Item::new(name.into());
+        item.set_done(done);
+        item.set_important(important);
+
+        // Items before any heading go into a list of their own.
+        if lists.is_empty() {
+            lists.push(List::new(IMPORTED.into()));
+        }
+        // Items only go one level deep, so anything indented at all is a
+        // sub-item of the last top-level item.
+        let list = lists.last_mut().unwrap();
+        match list.items.last_mut() {
+            Some(parent) if indent > 0 => parent.children.push(item),
+            _ => list.items.push(item),
+        }
+    }
+    // Headings without any tasks under them aren't lists.
+    lists.retain(|list| !list.items.is_empty());
+    lists
+}
+
+#[derive(Default)]
+/// The window for pasting in Markdown to import.
+pub struct Importer {
+    /// Whether or not the window is showing.
+    pub open: bool,
+    /// The Markdown that was pasted in.
+    text: String,
+}
+
+impl Importer {
+    /// Draw the window. Returns the imported lists once the user confirms.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Vec<List>> {
+        let mut open = self.open;
+        let mut imported = None;
+        egui::Window::new("Import from Markdown")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Paste a Markdown task list below.");
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.text)
+                                .hint_text("## List\n- [ ] item")
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                let lists = import(&self.text);
+                let items: usize = lists.iter().map(|list| list.items.len()).sum();
+                ui.label(
+                    egui::RichText::new(format!("{} lists, {items} items", lists.len())).weak(),
+                );
+                if ui
+                    .add_enabled(items > 0, egui::Button::new("Import"))
+                    .clicked()
+                {
+                    imported = Some(lists);
+                }
+            });
+        if imported.is_some() {
+            open = false;
+            self.text.clear();
+        }
+        self.open = open;
+        imported
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, done: bool, important: bool) -> Item {
+        let mut item = Item::new(name.into());
+        item.set_done(done);
+        item.set_important(important);
+        item
+    }
+
+    #[test]
+    fn pasted_lines_lose_their_bullets_and_checkboxes() {
+        assert_eq!(parse_line("milk"), Some(("milk", false)));
+        assert_eq!(parse_line("  - eggs "), Some(("eggs", false)));
+        assert_eq!(parse_line("* [ ] bread"), Some(("bread", false)));
+        assert_eq!(parse_line("+ [x] butter"), Some(("butter", true)));
+        assert_eq!(parse_line("[X] jam"), Some(("jam", true)));
+        assert_eq!(parse_line("-  [x]   tea"), Some(("tea", true)));
+    }
+
+    #[test]
+    fn inline_markup_is_kept_in_pasted_lines() {
+        assert_eq!(parse_line("- **oat** milk"), Some(("**oat** milk", false)));
+        assert_eq!(
+            parse_line("- [ ] see [recipe](https://example.com)"),
+            Some(("see [recipe](https://example.com)", false))
+        );
+        assert_eq!(
+            parse_line("- `x` marks the spot"),
+            Some(("`x` marks the spot", false))
+        );
+        // Only one bullet comes off, and only at the start.
+        assert_eq!(parse_line("- - dashes"), Some(("- dashes", false)));
+        assert_eq!(parse_line("-dash"), Some(("-dash", false)));
+    }
+
+    #[test]
+    fn empty_pasted_lines_are_skipped() {
+        for line in ["", "   ", "-", "- ", "- [ ]", "* [x]  "] {
+            assert_eq!(parse_line(line), None, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn pasted_blocks_name_their_list_on_the_first_line() {
+        assert_eq!(
+            split_list("Groceries:\n- milk\n- eggs"),
+            (Some("Groceries"), "- milk\n- eggs")
+        );
+        assert_eq!(
+            split_list("  Groceries :  \nmilk\n"),
+            (Some("Groceries"), "milk")
+        );
+        assert_eq!(split_list("milk\neggs"), (None, "milk\neggs"));
+        assert_eq!(split_list(":\nmilk"), (None, ":\nmilk"));
+        assert_eq!(split_list("Groceries:"), (Some("Groceries"), ""));
+    }
+
+    #[test]
+    fn lists_are_written_as_task_lists() {
+        let mut list = List::new("Groceries".into());
+        let mut bread = item("Bread", false, true);
+        bread.children.push(item("Rye", true, false));
+        list.items = vec![item("Milk", true, false), bread];
+        assert_eq!(
+            export(&[list]),
+            "## Groceries\n\n- [x] Milk\n- [ ] **Bread**\n  - [x] Rye\n"
+        );
+    }
+
+    #[test]
+    fn exported_lists_import_the_same() {
+        let mut groceries = List::new("Groceries".into());
+        let mut bread = item("Bread", false, true);
+        bread.children.push(item("Rye", false, false));
+        bread.children.push(item("Sourdough", true, false));
+        groceries.items = vec![item("Milk", true, false), bread, item("Eggs", false, false)];
+        let mut chores = List::new("Chores".into());
+        chores.items = vec![item("Laundry", false, false)];
+        let lists = [groceries, chores];
+
+        let imported = import(&export(&lists));
+        assert_eq!(export(&imported), export(&lists));
+        let bread = &imported[0].items[1];
+        assert!(bread.is_important && !bread.is_done);
+        assert_eq!(bread.children[1].name, "Sourdough");
+        assert!(bread.children[1].is_done);
+        assert_eq!(imported[0].items[2].name, "Eggs");
+    }
+
+    #[test]
+    fn deeper_tasks_are_sub_items_of_the_top_level_one() {
+        let lists = import(
+            "# Trip
+- [ ] Pack
+  - [ ] Clothes
+    - [ ] Socks
+- [ ] Go
+",
+        );
+        let pack = &lists[0].items[0];
+        let names: Vec<&str> = pack
+            .children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect();
+        assert_eq!(names, ["Clothes", "Socks"]);
+        assert!(pack.children.iter().all(|child| child.children.is_empty()));
+        assert_eq!(lists[0].items[1].name, "Go");
+    }
+
+    #[test]
+    fn tasks_before_any_heading_get_a_list_of_their_own() {
+        let lists =
+            import("- [ ] Loose end\n\n# Empty\n\nJust some notes\n\n# Work\n* [X] Report\n");
+        let names: Vec<&str> = lists.iter().map(|list| list.name.as_str()).collect();
+        assert_eq!(names, [IMPORTED, "Work"]);
+        assert!(lists[1].items[0].is_done);
+    }
+}
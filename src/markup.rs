@@ -0,0 +1,123 @@
+use eframe::egui::{self, text::LayoutJob, FontId, Stroke, TextFormat};
+
+/// The kinds of formatting that can show up in names and notes.
+enum Span {
+    /// `**bold**`
+    Bold,
+    /// `*italic*` or `_italic_`
+    Italic,
+    /// `` `code` ``
+    Code,
+    /// `[text](url)`, which only shows its text.
+    Link,
+}
+
+/// Where the text of a span starting at `start` (just after its opening
+/// `marker`) ends, if it's closed. Spans can't be empty, or start or end with
+/// a space, so that things like "2 * 3 * 4" are left alone.
+fn closing(text: &str, start: usize, marker: &str) -> Option<usize> {
+    let end = start + text[start..].find(marker)?;
+    let inner = &text[start..end];
+    (!inner.is_empty() && !inner.starts_with(' ') && !inner.ends_with(' ')).then_some(end)
+}
+
+/// The span starting at `at` in `text`, if there is one: where its text
+/// starts and ends, where whatever comes after it starts, and what kind it is.
+fn span(text: &str, at: usize) -> Option<(usize, usize, usize, Span)> {
+    let rest = &text[at..];
+    // Underscores in the middle of words, like in snake_case, aren't italics.
+    let word_before = text[..at]
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric);
+    if rest.starts_with("**") {
+        let end = closing(text, at + 2, "**")?;
+        Some((at + 2, end, end + 2, Span::Bold))
+    } else if rest.starts_with('*') || (rest.starts_with('_') && !word_before) {
+        let end = closing(text, at + 1, &rest[..1])?;
+        Some((at + 1, end, end + 1, Span::Italic))
+    } else if rest.starts_with('`') {
+        let end = closing(text, at + 1, "`")?;
+        Some((at + 1, end, end + 1, Span::Code))
+    } else if rest.starts_with('[') {
+        let middle = at + rest.find("](")?;
+        let end = middle + 2 + text[middle + 2..].find(')')?;
+        (middle > at + 1).then_some((at + 1, middle, end + 1, Span::Link))
+    } else {
+        None
+    }
+}
+
+/// Lay out `text` in `format`, with lightweight Markdown for bold, italics,
+/// inline code and links. Anything that isn't closed shows up as typed.
+pub fn layout(text: &str, format: TextFormat, visuals: &egui::Visuals) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    // Where the plain text that hasn't been added yet starts.
+    let mut plain = 0;
+    let mut at = 0;
+    while let Some(c) = text[at..].chars().next() {
+        let (start, end, next, kind) = match span(text, at) {
+            Some(span) => span,
+            None => {
+                at += c.len_utf8();
+                continue;
+            }
+        };
+        if plain < at {
+            job.append(&text[plain..at], 0.0, format.clone());
+        }
+        let mut styled = format.clone();
+        match kind {
+            // There's no bold font, so bold text is drawn brighter instead,
+            // unless it's been dimmed.
+            Span::Bold if format.color == visuals.text_color() => {
+                styled.color = visuals.strong_text_color();
+            }
+            Span::Bold => {}
+            Span::Italic => styled.italics = true,
+            Span::Code => {
+                styled.font_id = FontId::monospace(format.font_id.size);
+                styled.background = visuals.code_bg_color;
+            }
+            Span::Link => {
+                styled.color = visuals.hyperlink_color;
+                styled.underline = Stroke::new(1.0, visuals.hyperlink_color);
+            }
+        }
+        job.append(&text[start..end], 0.0, styled);
+        at = next;
+        plain = next;
+    }
+    if plain < text.len() {
+        job.append(&text[plain..], 0.0, format);
+    }
+    job
+}
+
+/// Every web address in `text`, in order. Addresses starting with "www." are
+/// given "https://" so that they can be opened.
+pub fn urls(text: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = ["https://", "http://", "www."]
+        .iter()
+        .filter_map(|prefix| rest.find(prefix))
+        .min()
+    {
+        let tail = &rest[start..];
+        // Addresses end at whitespace or brackets, like around a Markdown
+        // link, and punctuation at the very end belongs to the sentence.
+        let len = tail
+            .find(|c: char| c.is_whitespace() || "()[]<>\"'".contains(c))
+            .unwrap_or(tail.len());
+        let url = tail[..len].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        match url.strip_prefix("www.") {
+            Some(host) if host.contains('.') => urls.push(format!("https://{url}")),
+            Some(_) => {}
+            None if url.contains("://") && !url.ends_with("://") => urls.push(url.into()),
+            None => {}
+        }
+        rest = &tail[len.max(1)..];
+    }
+    urls
+}
@@ -0,0 +1,157 @@
+use serde_json::{Map, Value};
+
+/// The version of the data file written by this version of todoish.
+pub const VERSION: u64 = 2;
+
+/// The contents of a data file, as JSON.
+pub type Document = Map<String, Value>;
+
+/// A change to the data file from one version to the next, made in place.
+type Step = fn(&mut Document);
+
+/// The first version that was a document, rather than only the lists.
+const FIRST_DOCUMENT: u64 = 2;
+
+/// The changes from each version to the next, starting with version 2 to 3.
+/// A change to the format that older versions couldn't read gets a new
+/// version, along with a step here to bring older files up to it.
+const STEPS: &[Step] = &[];
+
+// Every version after the first document needs a step to get to it.
+const _: () = assert!(VERSION == FIRST_DOCUMENT + STEPS.len() as u64);
+
+/// The first version was only the lists, which left no room for anything else.
+fn from_v1(lists: Vec<Value>) -> Document {
+    let mut doc = Map::new();
+    doc.insert("version".into(), FIRST_DOCUMENT.into());
+    doc.insert("lists".into(), Value::Array(lists));
+    doc
+}
+
+/// Bring a data file written by any older version of todoish up to date, one
+/// version at a time, returning it along with the version it started out as.
+/// A file from a newer version can't be, so its version is the error instead.
+/// Anything that isn't a data file at all comes back as None.
+pub fn upgrade(value: Value) -> Result<Option<(Document, u64)>, u64> {
+    upgrade_with(value, STEPS)
+}
+
+/// Bring a data file up to date with `steps`, like `upgrade`.
+fn upgrade_with(value: Value, steps: &[Step]) -> Result<Option<(Document, u64)>, u64> {
+    let latest = FIRST_DOCUMENT + steps.len() as u64;
+    let (mut doc, from) = match value {
+        Value::Array(lists) => (from_v1(lists), 1),
+        Value::Object(doc) => {
+            let version = doc.get("version").and_then(Value::as_u64).unwrap_or(1);
+            (doc, version)
+        }
+        _ => return Ok(None),
+    };
+    if from > latest {
+        return Err(from);
+    }
+    // Files without a version (from before there was one) were written the
+    // same way as version 2, but are still older than it.
+    let done = (from.max(FIRST_DOCUMENT) - FIRST_DOCUMENT) as usize;
+    for step in &steps[done..] {
+        step(&mut doc);
+    }
+    doc.insert("version".into(), latest.into());
+    Ok(Some((doc, from)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::List;
+    use serde_json::json;
+
+    /// The names of the lists in an upgraded document.
+    fn names(doc: &Document) -> Vec<String> {
+        let lists: Vec<List> = serde_json::from_value(doc["lists"].clone()).unwrap();
+        lists.into_iter().map(|list| list.name).collect()
+    }
+
+    #[test]
+    fn version_1_files_round_trip() {
+        let lists = vec![List::new("Groceries".into()), List::new("Chores".into())];
+        let value = serde_json::to_value(&lists).unwrap();
+        let (doc, from) = upgrade(value).unwrap().unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(doc["version"], VERSION);
+        assert_eq!(names(&doc), ["Groceries", "Chores"]);
+    }
+
+    #[test]
+    fn version_2_files_round_trip() {
+        let lists = vec![List::new("Groceries".into())];
+        let value = json!({ "version": 2, "lists": lists, "trash": [] });
+        let (doc, from) = upgrade(value.clone()).unwrap().unwrap();
+        assert_eq!(from, 2);
+        assert_eq!(Value::Object(doc.clone()), value);
+        assert_eq!(names(&doc), ["Groceries"]);
+    }
+
+    #[test]
+    fn files_without_a_version_are_version_1() {
+        let (doc, from) = upgrade(json!({ "lists": [] })).unwrap().unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(doc["version"], VERSION);
+    }
+
+    #[test]
+    fn newer_files_are_left_alone() {
+        assert_eq!(
+            upgrade(json!({ "version": VERSION + 1 })).unwrap_err(),
+            VERSION + 1
+        );
+    }
+
+    /// Pretend version 3 renamed "lists" to "projects".
+    fn to_v3(doc: &mut Document) {
+        let lists = doc.remove("lists").unwrap();
+        doc.insert("projects".into(), lists);
+    }
+
+    /// Pretend version 4 added a "settings" object.
+    fn to_v4(doc: &mut Document) {
+        doc.insert("settings".into(), json!({}));
+    }
+
+    #[test]
+    fn upgrades_take_every_step_after_the_file_version() {
+        let steps: &[Step] = &[to_v3, to_v4];
+        let (doc, from) = upgrade_with(json!([]), steps).unwrap().unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(
+            Value::Object(doc),
+            json!({ "version": 4, "projects": [], "settings": {} })
+        );
+
+        let v2 = json!({ "version": 2, "lists": [] });
+        let (doc, _) = upgrade_with(v2, steps).unwrap().unwrap();
+        assert_eq!(
+            Value::Object(doc),
+            json!({ "version": 4, "projects": [], "settings": {} })
+        );
+
+        let v3 = json!({ "version": 3, "projects": [] });
+        let (doc, from) = upgrade_with(v3, steps).unwrap().unwrap();
+        assert_eq!(from, 3);
+        assert_eq!(
+            Value::Object(doc),
+            json!({ "version": 4, "projects": [], "settings": {} })
+        );
+
+        let v4 = json!({ "version": 4, "projects": [], "settings": { "x": 1 } });
+        let (doc, _) = upgrade_with(v4.clone(), steps).unwrap().unwrap();
+        assert_eq!(Value::Object(doc), v4);
+
+        assert_eq!(upgrade_with(json!({ "version": 5 }), steps).unwrap_err(), 5);
+    }
+
+    #[test]
+    fn anything_else_is_not_a_data_file() {
+        assert!(upgrade(json!("todo")).unwrap().is_none());
+    }
+}
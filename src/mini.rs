@@ -0,0 +1,22 @@
+use eframe::egui;
+
+/// The size of the window in mini mode, which is also as small as it gets.
+pub const SIZE: egui::Vec2 = egui::vec2(300.0, 400.0);
+
+/// The size of the window when there's no other size to go back to.
+pub const NORMAL_SIZE: egui::Vec2 = egui::vec2(500.0, 500.0);
+
+/// How much smaller the text is in mini mode.
+const TEXT_SCALE: f32 = 0.85;
+
+/// Shrink (or restore) the text to fit more into the mini mode window.
+pub fn apply_style(ctx: &egui::Context, mini: bool) {
+    let mut style = (*ctx.style()).clone();
+    style.text_styles = egui::Style::default().text_styles;
+    if mini {
+        for font in style.text_styles.values_mut() {
+            font.size *= TEXT_SCALE;
+        }
+    }
+    ctx.set_style(style);
+}
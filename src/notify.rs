@@ -0,0 +1,81 @@
+use crate::{Item, List};
+use chrono::NaiveDate;
+use eframe::egui;
+use notify_rust::Notification;
+use serde::{Deserialize, Serialize};
+use std::{thread, time::Duration};
+
+/// How often to check for items that have come due.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+/// How far along an item is past its due date.
+pub enum Stage {
+    /// It's due today.
+    Due,
+    /// It was due yesterday or earlier.
+    Overdue,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+/// The last notification sent about an item. It's kept along with the due date
+/// it was about, so that changing the due date starts things over.
+pub struct Notified {
+    due: NaiveDate,
+    stage: Stage,
+}
+
+/// What a notification should be sent about for `item`, if anything. Items
+/// that are done or snoozed are left alone, and each stage is only notified
+/// about once. Something that's already overdue skips straight to saying so.
+pub fn pending(item: &Item, today: NaiveDate) -> Option<Stage> {
+    let due = item
+        .due
+        .filter(|_| !item.is_done && item.snoozed_until.is_none())?;
+    let stage = match (today - due).num_days() {
+        ..=-1 => return None,
+        0 => Stage::Due,
+        _ => Stage::Overdue,
+    };
+    match item.notified {
+        Some(notified) if notified.due == due && notified.stage >= stage => None,
+        _ => Some(stage),
+    }
+}
+
+/// Send a notification for every item that's come due (or overdue) since the
+/// last check. Returns whether or not any item was notified about.
+pub fn check(lists: &mut [List], today: NaiveDate) -> bool {
+    let mut changed = false;
+    for list in lists {
+        for item in &mut list.items {
+            if let (Some(stage), Some(due)) = (pending(item, today), item.due) {
+                let summary = match stage {
+                    Stage::Due => "Due today",
+                    Stage::Overdue => "Overdue",
+                };
+                let result = Notification::new()
+                    .appname("todoish")
+                    .summary(summary)
+                    .body(&format!("{}: {}", list.name, item.name))
+                    .show();
+                if let Err(err) = result {
+                    eprintln!("Failed to show notification: {err}");
+                }
+                item.notified = Some(Notified { due, stage });
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Wake `ctx` up every so often, so that the checks still happen while nobody's
+/// touching the app.
+pub fn keep_awake(ctx: egui::Context) {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        ctx.request_repaint();
+    });
+}
@@ -0,0 +1,141 @@
+use crate::{Item, List};
+use eframe::egui;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+/// A step of the first-run onboarding.
+pub enum Step {
+    /// The welcome window, which offers to create a sample list.
+    Welcome,
+    /// Points at the text box for creating lists.
+    NewList,
+    /// Points at the header of a list.
+    Header,
+    /// Points at the saved indicator.
+    Saved,
+}
+
+impl Step {
+    /// The step after this one, or `None` if this is the last.
+    pub fn next(self) -> Option<Self> {
+        match self {
+            Self::Welcome => Some(Self::NewList),
+            Self::NewList => Some(Self::Header),
+            Self::Header => Some(Self::Saved),
+            Self::Saved => None,
+        }
+    }
+}
+
+#[derive(Default)]
+/// Where the things pointed at by the tour were drawn this frame.
+pub struct Targets {
+    /// The text box for creating lists.
+    pub new_list: Option<egui::Rect>,
+    /// The header of the first list.
+    pub header: Option<egui::Rect>,
+    /// The saved indicator.
+    pub saved: Option<egui::Rect>,
+}
+
+/// What the user chose to do in the onboarding.
+pub enum Action {
+    /// Create the sample list.
+    AddSample,
+    /// Go to the given step, or finish if there are no more.
+    GoTo(Option<Step>),
+}
+
+/// A list whose items explain how to use the app.
+pub fn welcome_list() -> List {
+    let mut list = List::new("Welcome to todoish".into());
+    list.items = [
+        "Check me off!",
+        "Double-click me to rename",
+        "Right-click me for more options",
+        "Right-click the list name to delete this list",
+        "Right-click the title bar for settings",
+    ]
+    .into_iter()
+    .map(|name| Item::new(name.into()))
+    .collect();
+    list
+}
+
+/// Draw the current onboarding step.
+pub fn show(ctx: &egui::Context, step: Step, targets: &Targets) -> Option<Action> {
+    let (target, text) = match step {
+        Step::Welcome => return welcome(ctx, step),
+        Step::NewList => (
+            targets.new_list,
+            "Type a name here and press Enter to create a list.",
+        ),
+        Step::Header => (
+            targets.header,
+            "Click a list's name to fold it away. Right-click it for more options.",
+        ),
+        Step::Saved => (
+            targets.saved,
+            "Your lists are saved automatically. This shows whether the latest changes are on disk yet.",
+        ),
+    };
+    // Nothing to point at (like a header when there are no lists), so move along.
+    let target = match target {
+        Some(target) => target,
+        None => return Some(Action::GoTo(step.next())),
+    };
+
+    let mut action = None;
+    egui::Area::new("onboarding tour")
+        .order(egui::Order::Foreground)
+        .fixed_pos(target.left_bottom() + egui::vec2(0.0, 6.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(260.0);
+                ui.label(text);
+                ui.horizontal(|ui| {
+                    let last = step.next().is_none();
+                    if ui.button(if last { "Done" } else { "Next" }).clicked() {
+                        action = Some(Action::GoTo(step.next()));
+                    }
+                    if !last && ui.small_button("Skip tour").clicked() {
+                        action = Some(Action::GoTo(None));
+                    }
+                });
+            });
+        });
+    // Outline what's being talked about.
+    ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("onboarding target"),
+    ))
+    .rect_stroke(
+        target.expand(3.0),
+        4.0,
+        ctx.style().visuals.selection.stroke,
+    );
+    action
+}
+
+/// Draw the welcome window.
+fn welcome(ctx: &egui::Context, step: Step) -> Option<Action> {
+    let mut action = None;
+    egui::Window::new("Welcome to todoish!")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label("Would you like a sample list that shows you around?");
+            ui.horizontal(|ui| {
+                if ui.button("Add sample list").clicked() {
+                    action = Some(Action::AddSample);
+                }
+                if ui.button("Start empty").clicked() {
+                    action = Some(Action::GoTo(step.next()));
+                }
+                if ui.small_button("Skip tour").clicked() {
+                    action = Some(Action::GoTo(None));
+                }
+            });
+        });
+    action
+}
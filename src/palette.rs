@@ -0,0 +1,227 @@
+use crate::List;
+use eframe::egui;
+
+/// How many results are shown at once.
+const MAX_RESULTS: usize = 20;
+
+/// How well `query` matches `text`, if every character of `query` shows up in
+/// `text` in order (ignoring case). Higher is better. Characters that follow
+/// each other, or that start a word, count for more, and gaps count against.
+pub fn score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+        let found = pos + text[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        match last {
+            Some(last) if found == last + 1 => score += 5,
+            Some(last) => score -= (found - last - 1).min(5) as i32,
+            None => score -= found.min(5) as i32,
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+/// Something the palette can take you to.
+pub enum Target {
+    /// The list at this index.
+    List(usize),
+    /// The (list, item) indices of an item.
+    Item(usize, usize),
+}
+
+/// What the user picked in the palette.
+pub enum Outcome {
+    /// Go to this list or item.
+    Jump(Target),
+    /// Check or uncheck the item at these (list, item) indices.
+    Toggle(usize, usize),
+}
+
+/// Every list and item matching `query`, best first.
+fn results(lists: &[List], query: &str) -> Vec<(Target, String)> {
+    let mut results = Vec::new();
+    for (list_idx, list) in lists.iter().enumerate() {
+        if let Some(score) = score(query, &list.name) {
+            results.push((score, Target::List(list_idx), list.name.clone()));
+        }
+        for (item_idx, item) in list.items.iter().enumerate() {
+            if let Some(score) = score(query, &item.name) {
+                let label = format!("{} · {}", item.name, list.name);
+                results.push((score, Target::Item(list_idx, item_idx), label));
+            }
+        }
+    }
+    // The sort is stable, so ties stay in the order they're drawn in.
+    results.sort_by_key(|&(score, ..)| -score);
+    results.truncate(MAX_RESULTS);
+    results
+        .into_iter()
+        .map(|(_, target, label)| (target, label))
+        .collect()
+}
+
+#[derive(Default)]
+/// The state of the "jump to" palette.
+pub struct Palette {
+    /// Whether or not the palette is showing.
+    pub open: bool,
+    /// What's been typed so far.
+    query: String,
+    /// Which result is picked, counting from the top.
+    selected: usize,
+    /// Whatever had the keyboard focus before the palette opened, to give it
+    /// back afterwards.
+    return_focus: Option<egui::Id>,
+}
+
+impl Palette {
+    /// Show the palette, starting over with an empty query.
+    pub fn open(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            self.return_focus = ctx.memory().focus();
+        }
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Hide the palette, giving the focus back to where it was.
+    fn close(&mut self, ctx: &egui::Context) {
+        self.open = false;
+        if let Some(id) = self.return_focus.take() {
+            ctx.memory().request_focus(id);
+        }
+    }
+
+    /// Draw the palette. Arrow keys move through the results, Enter goes to
+    /// the picked one, and Ctrl+Enter checks or unchecks it instead.
+    pub fn show(&mut self, ctx: &egui::Context, lists: &[List]) -> Option<Outcome> {
+        if !self.open {
+            return None;
+        }
+        let (up, down, enter, command, escape) = {
+            let input = ctx.input();
+            (
+                input.key_pressed(egui::Key::ArrowUp),
+                input.key_pressed(egui::Key::ArrowDown),
+                input.key_pressed(egui::Key::Enter),
+                input.modifiers.command,
+                input.key_pressed(egui::Key::Escape),
+            )
+        };
+        if escape {
+            self.close(ctx);
+            return None;
+        }
+
+        let results = results(lists, &self.query);
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let mut clicked = None;
+        egui::Window::new("Jump to")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                let before = self.query.clone();
+                let resp = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("jump to a list or item")
+                        .desired_width(f32::INFINITY),
+                );
+                // Typing always goes here while the palette is open.
+                resp.request_focus();
+                if self.query != before {
+                    self.selected = 0;
+                }
+                for (idx, (target, label)) in results.iter().enumerate() {
+                    let text = match target {
+                        Target::List(_) => egui::RichText::new(label).strong(),
+                        Target::Item(..) => egui::RichText::new(label),
+                    };
+                    if ui.selectable_label(idx == self.selected, text).clicked() {
+                        clicked = Some(*target);
+                    }
+                }
+                if results.is_empty() {
+                    ui.label(egui::RichText::new("no matches").weak());
+                }
+            });
+
+        let picked = results.get(self.selected).map(|&(target, _)| target);
+        if enter && command {
+            return match picked {
+                Some(Target::Item(list, item)) => Some(Outcome::Toggle(list, item)),
+                _ => None,
+            };
+        }
+        let target = clicked.or(picked.filter(|_| enter))?;
+        // Jumping goes to the lists, so the focus isn't given back.
+        self.open = false;
+        self.return_focus = None;
+        Some(Outcome::Jump(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Item;
+
+    #[test]
+    fn every_character_has_to_show_up_in_order() {
+        assert!(score("gro", "Groceries").is_some());
+        assert!(score("GRO", "groceries").is_some());
+        assert!(score("gcs", "Groceries").is_some());
+        assert!(score("sg", "Groceries").is_none());
+        assert!(score("groceriesx", "Groceries").is_none());
+    }
+
+    #[test]
+    fn runs_and_word_starts_count_for_more() {
+        // Characters in a row beat ones spread out.
+        assert!(score("milk", "milk") > score("milk", "my ilk"));
+        // The start of a word beats the middle of one.
+        assert!(score("m", "buy milk") > score("m", "hammer"));
+        // Gaps count against, but only so much.
+        assert!(score("ab", "a--b") > score("ab", "a-------b"));
+        assert_eq!(score("ab", "a------b"), score("ab", "a-----------b"));
+    }
+
+    #[test]
+    fn spaces_in_the_query_are_ignored() {
+        assert_eq!(score("bu mi", "buy milk"), score("bumi", "buy milk"));
+        assert_eq!(score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn results_are_best_first() {
+        let mut groceries = List::new("Groceries".into());
+        groceries.items = vec![Item::new("Gravy".into()), Item::new("Grocery bags".into())];
+        let mut chores = List::new("Chores".into());
+        chores.items = vec![Item::new("Mow the lawn".into())];
+        let found = results(&[groceries, chores], "groc");
+        let targets: Vec<Target> = found.iter().map(|&(target, _)| target).collect();
+        assert!(targets == [Target::List(0), Target::Item(0, 1)]);
+        assert_eq!(found[1].1, "Grocery bags · Groceries");
+    }
+}
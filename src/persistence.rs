@@ -0,0 +1,972 @@
+use crate::{
+    crypto,
+    migrate::{self, VERSION},
+    settings::Settings,
+    sync, trash, List,
+};
+use chrono::{Local, NaiveDateTime};
+use dirs::{data_dir, home_dir};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{mpsc, OnceLock},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Where the todo lists used to be saved, before moving to the platform's data directory.
+fn legacy_path() -> PathBuf {
+    let mut path = home_dir().expect("Failed to find home directory");
+    path.push(".todoish");
+    path
+}
+
+/// Figure out where the todo lists should be saved. In order of preference,
+/// that's the `TODOISH_DATA` environment variable, the `data_path` setting,
+/// or `todoish/todoish.json` in the platform's data directory. If the lists
+/// can't be put there, they stay at the old path, along with why.
+fn resolve_data_path() -> (PathBuf, Option<String>) {
+    if let Some(path) = env::var_os("TODOISH_DATA") {
+        return (path.into(), None);
+    }
+    if let Some(path) = Settings::load().data_path {
+        return (path, None);
+    }
+
+    let mut path = data_dir().unwrap_or_else(|| home_dir().expect("Failed to find home directory"));
+    path.push("todoish");
+    let legacy = legacy_path();
+    if let Err(err) = fs::create_dir_all(&path) {
+        let problem = format!(
+            "{} couldn't be created ({err}), so your lists are being kept at {} instead.",
+            path.display(),
+            legacy.display()
+        );
+        return (legacy, Some(problem));
+    }
+    path.push("todoish.json");
+
+    // Bring over the lists from the old location the first time around. The old
+    // file is left where it is, as a backup.
+    if !path.exists() && legacy.exists() {
+        if let Err(err) = fs::copy(&legacy, &path) {
+            let problem = format!(
+                "Your lists couldn't be copied to {} ({err}), so they're still being kept at {}.",
+                path.display(),
+                legacy.display()
+            );
+            return (legacy, Some(problem));
+        }
+    }
+    (path, None)
+}
+
+/// Where the todo lists are saved, and why it's not the usual place if it isn't.
+fn resolved_data_path() -> &'static (PathBuf, Option<String>) {
+    static PATH: OnceLock<(PathBuf, Option<String>)> = OnceLock::new();
+    PATH.get_or_init(resolve_data_path)
+}
+
+/// The path of the file that the todo lists are saved to.
+pub fn data_path() -> PathBuf {
+    resolved_data_path().0.clone()
+}
+
+/// Why the todo lists are saved at the old path instead of the platform's data
+/// directory, if they had to be.
+pub fn data_path_problem() -> Option<String> {
+    resolved_data_path().1.clone()
+}
+
+/// The path that saves are written to before replacing the real file.
+fn tmp_path() -> PathBuf {
+    let mut path = data_path();
+    path.set_extension("tmp");
+    path
+}
+
+/// The directory that old copies of the data file are kept in, beside the
+/// data file itself. It's named after the data file, so that nothing else
+/// would be kept in it.
+fn backups_dir() -> PathBuf {
+    let mut path = data_path().into_os_string();
+    path.push(".backups");
+    PathBuf::from(path)
+}
+
+/// How backups are named, after when they were made.
+const BACKUP_NAME: &str = "%Y%m%d-%H%M%S.bak";
+
+/// When the backup at `path` was made, going by its name. Its modification
+/// time can't be trusted for this, since copying or restoring it might change
+/// it (or not).
+fn made_at(path: &Path) -> Option<NaiveDateTime> {
+    let name = path.file_name()?.to_str()?;
+    NaiveDateTime::parse_from_str(name, BACKUP_NAME).ok()
+}
+
+/// Whether or not `path` is named like a backup, which is all that's ever
+/// listed (or thrown out) in the backups directory.
+fn is_backup(path: &Path) -> bool {
+    made_at(path).is_some()
+}
+
+/// How often the data file is backed up, at most. Saves happen every few
+/// seconds, which would otherwise push out anything older in no time.
+const BACKUP_EVERY: Duration = Duration::from_secs(60 * 60);
+
+/// Every backup of the data file, oldest first.
+fn backups() -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = fs::read_dir(backups_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && is_backup(path))
+                .collect()
+        })
+        .unwrap_or_default();
+    // The names start with when they were made, so this sorts them by age.
+    backups.sort();
+    backups
+}
+
+/// The newest backup of the data file, if there are any.
+pub fn latest_backup() -> Option<PathBuf> {
+    backups().pop()
+}
+
+/// Read the lists (and the trash) out of a backup of the data file. Unlike
+/// `load_lists`, a backup that can't be read completely is an error.
+pub fn read_backup(path: &Path) -> Result<(Vec<List>, Vec<trash::Entry>), String> {
+    let bytes = fs::read(path).map_err(|err| err.to_string())?;
+    match parse_lists(&crypto::decode(bytes)?) {
+        Ok(parsed) if parsed.complete => Ok((parsed.lists, parsed.trash)),
+        Ok(_) => Err("The backup is broken too".into()),
+        Err(version) => Err(format!(
+            "The backup is from a newer version of todoish (version {version})"
+        )),
+    }
+}
+
+/// Copy the data file into the backups directory, unless the last copy is
+/// recent enough, and throw out the oldest copies past the newest `keep`.
+fn back_up(keep: usize) -> io::Result<()> {
+    let data = data_path();
+    if !data.exists() {
+        return Ok(());
+    }
+    let mut backups = backups();
+    // A backup from the future (like after the clock was turned back) doesn't
+    // count as recent.
+    let recent = backups
+        .last()
+        .and_then(|path| made_at(path))
+        .and_then(|made| (Local::now().naive_local() - made).to_std().ok())
+        .is_some_and(|age| age < BACKUP_EVERY);
+    if keep > 0 && !recent {
+        let dir = backups_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(Local::now().format(BACKUP_NAME).to_string());
+        fs::copy(&data, &path)?;
+        backups.push(path);
+    }
+    let extra = backups.len().saturating_sub(keep);
+    for old in backups.drain(..extra) {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// When the file at `path` was last modified, if it exists.
+fn modified(path: PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[derive(Serialize)]
+/// What gets written to the data file. The first version was only the lists,
+/// which left no room for anything else.
+struct Document<'a> {
+    version: u64,
+    lists: &'a [List],
+    #[serde(skip_serializing_if = "<[trash::Entry]>::is_empty")]
+    trash: &'a [trash::Entry],
+}
+
+/// The todo lists read from disk.
+pub struct Loaded {
+    /// Every list that could be read.
+    pub lists: Vec<List>,
+    /// What's in the trash.
+    pub trash: Vec<trash::Entry>,
+    /// Where the data file was moved to if it couldn't be read completely, or
+    /// why it couldn't be moved.
+    pub backup: Option<io::Result<PathBuf>>,
+    /// Whether or not the data file was moved because a newer version of
+    /// todoish wrote it.
+    pub too_new: bool,
+    /// Why the data file couldn't be decrypted, if it was moved for that reason.
+    pub locked: Option<String>,
+    /// Whether or not the lists should be saved soon, even if nothing changes.
+    /// This is the case when lists or items from an older version were given ids,
+    /// or when the file was in an older format.
+    pub needs_save: bool,
+}
+
+impl Loaded {
+    /// Lists that were read without any trouble.
+    fn complete(lists: Vec<List>, trash: Vec<trash::Entry>) -> Self {
+        Self {
+            lists,
+            trash,
+            backup: None,
+            too_new: false,
+            locked: None,
+            needs_save: false,
+        }
+    }
+}
+
+/// Whether or not any list or item in `lists` was saved before they had ids.
+fn missing_ids(lists: &[Value]) -> bool {
+    fn item_missing(item: &Value) -> bool {
+        item.get("id").is_none()
+            || item["children"]
+                .as_array()
+                .is_some_and(|children| children.iter().any(item_missing))
+    }
+    let list_missing = |list: &Value| {
+        list.get("id").is_none()
+            || ["items", "archived"].into_iter().any(|key| {
+                list[key]
+                    .as_array()
+                    .is_some_and(|items| items.iter().any(item_missing))
+            })
+    };
+    lists.iter().any(list_missing)
+}
+
+/// The lists read out of a data file.
+struct Parsed {
+    /// Every list that could be read.
+    lists: Vec<List>,
+    /// Whatever could be read from the trash.
+    trash: Vec<trash::Entry>,
+    /// Whether or not everything could be read.
+    complete: bool,
+    /// Whether or not the lists should be saved again, because the file was in
+    /// an older format.
+    outdated: bool,
+    /// The version of the file.
+    version: u64,
+}
+
+/// Read as many lists as possible out of `bytes`, keeping those that work. A
+/// file written by a newer version of todoish isn't read at all, and its
+/// version is returned instead.
+fn parse_lists(bytes: &[u8]) -> Result<Parsed, u64> {
+    let broken = |version| Parsed {
+        lists: Vec::new(),
+        trash: Vec::new(),
+        complete: false,
+        outdated: false,
+        version,
+    };
+    // Older files are brought up to date before anything is read out of them.
+    let upgraded = match serde_json::from_slice(bytes) {
+        Ok(value) => migrate::upgrade(value)?,
+        Err(_) => None,
+    };
+    let (mut doc, version) = match upgraded {
+        Some(upgraded) => upgraded,
+        None => return Ok(broken(VERSION)),
+    };
+    // Anything in the trash that can't be read isn't worth losing the lists
+    // over, so it's just left out.
+    let trash = match doc.remove("trash") {
+        Some(Value::Array(trash)) => trash
+            .iter()
+            .filter_map(|value| trash::Entry::deserialize(value).ok())
+            .collect(),
+        _ => Vec::new(),
+    };
+    let values = match doc.remove("lists") {
+        Some(Value::Array(values)) => values,
+        _ => return Ok(broken(version)),
+    };
+    let lists: Vec<List> = values
+        .iter()
+        .filter_map(|value| List::deserialize(value).ok())
+        .collect();
+    Ok(Parsed {
+        complete: lists.len() == values.len(),
+        outdated: version < VERSION || missing_ids(&values),
+        lists,
+        trash,
+        version,
+    })
+}
+
+/// Read the contents of the data file, decrypted if need be, without touching
+/// anything. A missing file reads as no lists at all.
+pub fn read_raw() -> Result<Vec<u8>, String> {
+    match fs::read(data_path()) {
+        Ok(bytes) => crypto::decode(bytes),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(b"[]".to_vec()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Read the todo lists from disk, without touching anything. Unlike
+/// `load_lists`, a file that can't be read completely is an error.
+pub fn read_lists() -> Result<Vec<List>, String> {
+    read_all().map(|(lists, _)| lists)
+}
+
+/// Read the todo lists and the trash from disk, without touching anything,
+/// like `read_lists`.
+pub fn read_all() -> Result<(Vec<List>, Vec<trash::Entry>), String> {
+    match parse_lists(&read_raw()?) {
+        Ok(parsed) if parsed.complete => Ok((parsed.lists, parsed.trash)),
+        Ok(_) => Err("The data file is broken".into()),
+        Err(version) => Err(format!(
+            "The data file is from a newer version of todoish (version {version})"
+        )),
+    }
+}
+
+/// Read whatever lists can be read from disk, without touching anything.
+pub fn peek_lists() -> Vec<List> {
+    read_raw()
+        .ok()
+        .and_then(|bytes| parse_lists(&bytes).ok())
+        .map_or(Vec::new(), |parsed| parsed.lists)
+}
+
+/// Move the data file out of the way, so that it isn't overwritten by the next
+/// save. Returns where it went.
+fn move_aside(suffix: &str) -> io::Result<PathBuf> {
+    let mut backup = data_path().into_os_string();
+    backup.push(suffix);
+    let backup = PathBuf::from(backup);
+    fs::rename(data_path(), &backup)?;
+    Ok(backup)
+}
+
+/// Read the todo lists from disk. If the data file is broken (or from a newer
+/// version of todoish), it's moved out of the way so that it isn't overwritten
+/// by the next save.
+pub fn load_lists() -> Loaded {
+    // A leftover temporary file that's newer than the real one means a save was
+    // interrupted right before the rename, so it holds the latest changes.
+    if modified(tmp_path()) > modified(data_path()) {
+        let recovered = fs::read(tmp_path())
+            .ok()
+            .and_then(|bytes| crypto::decode(bytes).ok())
+            .and_then(|bytes| parse_lists(&bytes).ok())
+            .filter(|parsed| parsed.complete);
+        if let Some(parsed) = recovered {
+            // Finish the interrupted save.
+            return Loaded {
+                needs_save: true,
+                ..Loaded::complete(parsed.lists, parsed.trash)
+            };
+        }
+    }
+
+    // Default to an empty Vec if the file doesn't exist.
+    let bytes = match fs::read(data_path()) {
+        Ok(bytes) => bytes,
+        Err(_) => return Loaded::complete(Vec::new(), Vec::new()),
+    };
+    // An encrypted file that can't be decrypted is kept out of the way too,
+    // rather than being overwritten with nothing.
+    let bytes = match crypto::decode(bytes) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let suffix = format!(".encrypted-{}", Local::now().format("%Y%m%d-%H%M%S"));
+            return Loaded {
+                backup: Some(move_aside(&suffix)),
+                locked: Some(err),
+                ..Loaded::complete(Vec::new(), Vec::new())
+            };
+        }
+    };
+    let parsed = match parse_lists(&bytes) {
+        Ok(parsed) => parsed,
+        Err(version) => {
+            return Loaded {
+                backup: Some(move_aside(&format!(".v{version}"))),
+                too_new: true,
+                ..Loaded::complete(Vec::new(), Vec::new())
+            }
+        }
+    };
+    if parsed.complete {
+        // Keep a copy of the file in the old format, the first time it's upgraded.
+        if parsed.version < VERSION {
+            let mut backup = data_path().into_os_string();
+            backup.push(format!(".v{}.bak", parsed.version));
+            let backup = PathBuf::from(backup);
+            if !backup.exists() {
+                if let Err(err) = fs::copy(data_path(), backup) {
+                    eprintln!("Failed to back up data file: {err}");
+                }
+            }
+        }
+        return Loaded {
+            needs_save: parsed.outdated,
+            ..Loaded::complete(parsed.lists, parsed.trash)
+        };
+    }
+
+    let backup = move_aside(&format!(".broken-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    // The data file is gone now, so whatever could be read needs to be saved.
+    Loaded {
+        lists: parsed.lists,
+        trash: parsed.trash,
+        backup: Some(backup),
+        too_new: false,
+        locked: None,
+        needs_save: true,
+    }
+}
+
+/// Write the todo lists to disk. They're written to a temporary file first and
+/// then moved over the real one, so the real file is never left half-written,
+/// even if the app or the machine dies partway through.
+/// If encryption is on but the keyring can't be reached, nothing is written at
+/// all, rather than falling back to plain text. The file being replaced is
+/// backed up first, every so often.
+pub fn save_lists(lists: &[List], trash: &[trash::Entry], settings: &Settings) -> io::Result<()> {
+    let document = Document {
+        version: VERSION,
+        lists,
+        trash,
+    };
+    let mut bytes = serde_json::to_vec(&document).expect("Failed to serialize");
+    if settings.encrypt {
+        bytes = crypto::encrypt(&bytes).map_err(io::Error::other)?;
+    }
+    let tmp = tmp_path();
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    // A backup that can't be made isn't worth losing the save over.
+    if let Err(err) = back_up(settings.keep_backups as usize) {
+        eprintln!("Failed to back up data file: {err}");
+    }
+    fs::rename(tmp, data_path())?;
+    // On Unix the rename itself isn't on disk until the directory is flushed
+    // too, so a crash right after it could still bring back the old file. The
+    // save has happened either way, so a flush that fails isn't an error.
+    #[cfg(unix)]
+    if let Some(dir) = data_path()
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    {
+        if let Err(err) = fs::File::open(dir).and_then(|dir| dir.sync_all()) {
+            eprintln!("Failed to flush data directory: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// How long the save thread waits for things to settle before saving.
+const SAVE_DELAY: Duration = Duration::from_secs(3);
+
+/// A copy of the lists (and the trash) to save.
+struct Job {
+    /// Counts up with every copy, so it's clear which ones have been saved.
+    seq: u64,
+    lists: Vec<List>,
+    trash: Vec<trash::Entry>,
+    /// The app's settings, for how to save.
+    settings: Settings,
+    /// The lists as they were last saved or read, for merging with changes
+    /// made to the data file from elsewhere in the meantime.
+    base: Vec<List>,
+    /// When the data file was modified, as of the last time it was saved or
+    /// read when this copy was handed over.
+    synced: Option<SystemTime>,
+    /// Where to send the result, if someone's waiting for it.
+    reply: Option<mpsc::Sender<io::Result<Saved>>>,
+    /// Whether or not to save without waiting for things to settle.
+    hurry: bool,
+}
+
+impl Job {
+    /// Swap this copy for a newer one. The base stays the same, since nothing
+    /// in between was saved.
+    fn replace(self, newer: Job) -> Job {
+        Job {
+            base: self.base,
+            synced: self.synced,
+            reply: newer.reply.or(self.reply),
+            hurry: newer.hurry || self.hurry,
+            ..newer
+        }
+    }
+
+    /// Save this copy. If the data file was changed from somewhere else since
+    /// it was last saved or read (or since `written`, the last time this
+    /// thread wrote it), those changes are merged in first rather than saved
+    /// over.
+    fn save(self, written: Option<SystemTime>) -> io::Result<Saved> {
+        let on_disk = modified(data_path());
+        let merged = if on_disk.is_some() && on_disk != self.synced.max(written) {
+            read_raw()
+                .ok()
+                .and_then(|bytes| parse_lists(&bytes).ok())
+                .filter(|parsed| parsed.complete)
+                .map(|parsed| {
+                    let lists = sync::merge(&self.base, &self.lists, &parsed.lists);
+                    let mut trash = self.trash.clone();
+                    trash::merge(&mut trash, parsed.trash);
+                    Merged {
+                        sent: self.lists.clone(),
+                        lists,
+                        trash,
+                    }
+                })
+        } else {
+            None
+        };
+        match &merged {
+            Some(merged) => save_lists(&merged.lists, &merged.trash, &self.settings)?,
+            None => save_lists(&self.lists, &self.trash, &self.settings)?,
+        }
+        Ok(Saved {
+            modified: modified(data_path()),
+            merged,
+        })
+    }
+}
+
+/// Lists that were merged with changes made to the data file from elsewhere
+/// while they were waiting to be saved.
+pub struct Merged {
+    /// The lists that were handed over to be saved.
+    pub sent: Vec<List>,
+    /// What they were merged into, which is what was saved instead.
+    pub lists: Vec<List>,
+    /// The trash, merged the same way.
+    pub trash: Vec<trash::Entry>,
+}
+
+/// How a save went.
+struct Saved {
+    /// When the data file was modified by the save.
+    modified: Option<SystemTime>,
+    /// What was saved instead, if the data file was changed from elsewhere.
+    merged: Option<Merged>,
+}
+
+/// Saves the todo lists on a background thread, one save at a time.
+pub struct Saver {
+    tx: mpsc::Sender<Job>,
+    /// How the background saves went, oldest first, along with the newest
+    /// copy each of them included.
+    results: mpsc::Receiver<(u64, io::Result<Saved>)>,
+    /// The newest copy that was handed to the thread.
+    queued: u64,
+    /// The newest copy that's known to be on disk.
+    saved: u64,
+    /// When the data file was modified, as of the last time it was saved or
+    /// read. Anything else means it was changed from somewhere else.
+    synced: Option<SystemTime>,
+}
+
+impl Saver {
+    /// Start the thread that does the saving. `ctx` is woken up whenever a
+    /// background save finishes, so the result shows up right away.
+    pub fn new(ctx: egui::Context) -> Self {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let (results_tx, results) = mpsc::channel();
+        thread::spawn(move || {
+            // When this thread last wrote the data file, which isn't a change
+            // from elsewhere even if the app hasn't heard about it yet.
+            let mut written = None;
+            while let Ok(mut job) = rx.recv() {
+                // Give things a few seconds to settle, so that a burst of changes
+                // only means one save. Nobody waiting on a save has to wait for that.
+                let deadline = Instant::now() + SAVE_DELAY;
+                while job.reply.is_none() && !job.hurry {
+                    match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(newer) => job = job.replace(newer),
+                        Err(_) => break,
+                    }
+                }
+                // Only the newest copy matters if more have piled up in the meantime.
+                while let Ok(newer) = rx.try_recv() {
+                    job = job.replace(newer);
+                }
+                let seq = job.seq;
+                let reply = job.reply.take();
+                let result = job.save(written);
+                if let Ok(saved) = &result {
+                    written = saved.modified;
+                }
+                match reply {
+                    Some(reply) => {
+                        let _ = reply.send(result);
+                    }
+                    None => {
+                        if let Err(err) = &result {
+                            eprintln!("Failed to write to disk: {err}");
+                        }
+                        let _ = results_tx.send((seq, result));
+                        ctx.request_repaint();
+                    }
+                }
+            }
+        });
+        Self {
+            tx,
+            results,
+            queued: 0,
+            saved: 0,
+            synced: modified(data_path()),
+        }
+    }
+
+    /// Hand a copy of the lists to the thread, to be saved once things settle
+    /// down. `base` is the lists as they were last saved or read.
+    pub fn save(
+        &mut self,
+        lists: Vec<List>,
+        trash: Vec<trash::Entry>,
+        base: Vec<List>,
+        settings: &Settings,
+    ) {
+        self.queue(lists, trash, base, settings, false);
+    }
+
+    /// Hand a copy of the lists to the thread like `save`, but to be saved as
+    /// soon as it gets to it, along with anything still waiting to be saved.
+    pub fn save_soon(
+        &mut self,
+        lists: Vec<List>,
+        trash: Vec<trash::Entry>,
+        base: Vec<List>,
+        settings: &Settings,
+    ) {
+        self.queue(lists, trash, base, settings, true);
+    }
+
+    fn queue(
+        &mut self,
+        lists: Vec<List>,
+        trash: Vec<trash::Entry>,
+        base: Vec<List>,
+        settings: &Settings,
+        hurry: bool,
+    ) {
+        self.queued += 1;
+        let job = Job {
+            seq: self.queued,
+            lists,
+            trash,
+            settings: settings.clone(),
+            base,
+            synced: self.synced,
+            reply: None,
+            hurry,
+        };
+        self.tx.send(job).expect("Save thread stopped");
+    }
+
+    /// Save a copy of the lists, waiting until it's on disk. Returns what was
+    /// saved instead, if the lists were merged with changes from elsewhere.
+    pub fn save_now(
+        &mut self,
+        lists: Vec<List>,
+        trash: Vec<trash::Entry>,
+        base: Vec<List>,
+        settings: &Settings,
+    ) -> io::Result<Option<Merged>> {
+        self.queued += 1;
+        let (reply, result) = mpsc::channel();
+        let job = Job {
+            seq: self.queued,
+            lists,
+            trash,
+            settings: settings.clone(),
+            base,
+            synced: self.synced,
+            reply: Some(reply),
+            hurry: true,
+        };
+        self.tx.send(job).expect("Save thread stopped");
+        let saved = result.recv().expect("Save thread stopped")?;
+        self.synced = saved.modified;
+        self.saved = self.queued;
+        Ok(saved.merged)
+    }
+
+    /// How the most recent background save went, if any have finished since
+    /// the last time this was asked, along with every save since then that
+    /// was merged with changes from elsewhere, oldest first.
+    pub fn finished(&mut self) -> (Option<io::Result<()>>, Vec<Merged>) {
+        let mut last = None;
+        let mut merged = Vec::new();
+        for (seq, result) in self.results.try_iter() {
+            last = Some(result.map(|saved| {
+                self.saved = self.saved.max(seq);
+                self.synced = saved.modified;
+                merged.extend(saved.merged);
+            }));
+        }
+        (last, merged)
+    }
+
+    /// Whether or not some of the copies handed to the thread haven't been saved yet.
+    pub fn pending(&self) -> bool {
+        self.saved < self.queued
+    }
+
+    /// The lists (and trash) in the data file, if it's been changed from
+    /// somewhere else since it was last saved or read. Nothing is read while a
+    /// save is still on its way, and a file that can't be read completely is
+    /// left for the next save to replace.
+    pub fn changes(&mut self) -> Option<(Vec<List>, Vec<trash::Entry>)> {
+        let modified = modified(data_path());
+        if self.pending() || modified.is_none() || modified == self.synced {
+            return None;
+        }
+        self.synced = modified;
+        let parsed = read_raw()
+            .ok()
+            .and_then(|bytes| parse_lists(&bytes).ok())
+            .filter(|parsed| parsed.complete)?;
+        Some((parsed.lists, parsed.trash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Item;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// Point the data file into a fresh temporary directory. The tests share
+    /// the one data path, so they take turns with it.
+    fn temp_data() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let dir = env::temp_dir().join(format!("todoish-test-{}", std::process::id()));
+        env::set_var("TODOISH_DATA", dir.join("todoish.json"));
+        // Never touch a real data file.
+        assert!(data_path().starts_with(&dir));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        guard
+    }
+
+    /// A list named `name` with an item for each of `items`.
+    fn list(name: &str, items: &[&str]) -> List {
+        let mut list = List::new(name.into());
+        list.items = items.iter().map(|&item| Item::new(item.into())).collect();
+        list
+    }
+
+    /// The names of the items in each list, for comparing.
+    fn contents(lists: &[List]) -> Vec<(String, Vec<String>)> {
+        lists
+            .iter()
+            .map(|list| {
+                let items = list.items.iter().map(|item| item.name.clone()).collect();
+                (list.name.clone(), items)
+            })
+            .collect()
+    }
+
+    /// Set when the file at `path` was last modified.
+    fn set_modified(path: &Path, time: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(time))
+            .unwrap();
+    }
+
+    #[test]
+    fn backups_go_by_the_time_in_their_name() {
+        let _data = temp_data();
+        fs::write(data_path(), "[]").unwrap();
+        fs::create_dir_all(backups_dir()).unwrap();
+        // Written just now, but made two hours ago.
+        let made = Local::now() - chrono::Duration::hours(2);
+        fs::write(
+            backups_dir().join(made.format(BACKUP_NAME).to_string()),
+            "[]",
+        )
+        .unwrap();
+        back_up(10).unwrap();
+        assert_eq!(backups().len(), 2);
+        // The new one is recent enough that there's no need for another.
+        back_up(10).unwrap();
+        assert_eq!(backups().len(), 2);
+    }
+
+    #[test]
+    fn only_the_newest_backups_are_kept() {
+        let _data = temp_data();
+        fs::write(data_path(), "[]").unwrap();
+        fs::create_dir_all(backups_dir()).unwrap();
+        let now = Local::now();
+        let names: Vec<String> = (1..=5)
+            .map(|days| {
+                let made = now - chrono::Duration::days(days);
+                made.format(BACKUP_NAME).to_string()
+            })
+            .collect();
+        for name in &names {
+            fs::write(backups_dir().join(name), "[]").unwrap();
+        }
+        fs::write(backups_dir().join("notes.txt"), "mine").unwrap();
+        back_up(3).unwrap();
+        let kept: Vec<PathBuf> = backups();
+        assert_eq!(kept.len(), 3);
+        assert_eq!(kept[0], backups_dir().join(&names[1]));
+        assert_eq!(kept[1], backups_dir().join(&names[0]));
+        assert!(backups_dir().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn interrupted_saves_are_finished() {
+        let _data = temp_data();
+        let old = vec![list("Groceries", &["milk"])];
+        let new = vec![list("Groceries", &["milk", "eggs"])];
+        fs::write(data_path(), serde_json::to_vec(&old).unwrap()).unwrap();
+        fs::write(tmp_path(), serde_json::to_vec(&new).unwrap()).unwrap();
+        let now = SystemTime::now();
+        set_modified(&data_path(), now - Duration::from_secs(10));
+        set_modified(&tmp_path(), now);
+        let loaded = load_lists();
+        assert!(loaded.needs_save);
+        assert_eq!(contents(&loaded.lists), contents(&new));
+
+        // A leftover that's older than the data file has already been saved over.
+        set_modified(&tmp_path(), now - Duration::from_secs(20));
+        let loaded = load_lists();
+        assert_eq!(contents(&loaded.lists), contents(&old));
+    }
+
+    #[test]
+    fn broken_files_are_moved_aside() {
+        let _data = temp_data();
+        let groceries = serde_json::to_value(list("Groceries", &["milk"])).unwrap();
+        let broken = serde_json::json!([groceries, { "name": 7 }]);
+        fs::write(data_path(), broken.to_string()).unwrap();
+        let loaded = load_lists();
+        assert_eq!(
+            contents(&loaded.lists),
+            [("Groceries".into(), vec!["milk".into()])]
+        );
+        assert!(loaded.needs_save);
+        let moved = loaded.backup.unwrap().unwrap();
+        assert!(moved.to_string_lossy().contains(".broken-"));
+        assert_eq!(fs::read_to_string(moved).unwrap(), broken.to_string());
+        assert!(!data_path().exists());
+    }
+
+    #[test]
+    fn files_from_newer_versions_are_moved_aside() {
+        let _data = temp_data();
+        let newer = serde_json::json!({ "version": VERSION + 1, "lists": [] });
+        fs::write(data_path(), newer.to_string()).unwrap();
+        let loaded = load_lists();
+        assert!(loaded.too_new);
+        assert!(loaded.lists.is_empty());
+        let moved = loaded.backup.unwrap().unwrap();
+        assert!(moved
+            .to_string_lossy()
+            .ends_with(&format!(".v{}", VERSION + 1)));
+        assert!(!data_path().exists());
+    }
+
+    #[test]
+    fn older_files_are_kept_once_when_upgraded() {
+        let _data = temp_data();
+        let v1 = serde_json::to_vec(&vec![list("Groceries", &["milk"])]).unwrap();
+        fs::write(data_path(), &v1).unwrap();
+        let loaded = load_lists();
+        assert!(loaded.needs_save);
+        assert!(loaded.backup.is_none());
+        let mut kept = data_path().into_os_string();
+        kept.push(".v1.bak");
+        assert_eq!(fs::read(&kept).unwrap(), v1);
+        assert!(data_path().exists());
+    }
+
+    #[test]
+    fn saves_merge_in_changes_from_elsewhere() {
+        let _data = temp_data();
+        let settings = Settings::default();
+        let base = vec![list("Groceries", &["milk"])];
+        save_lists(&base, &[], &settings).unwrap();
+        let mut saver = Saver::new(egui::Context::default());
+
+        // Something else adds eggs, while the app adds bread.
+        let mut theirs = base.clone();
+        theirs[0].items.push(Item::new("eggs".into()));
+        save_lists(&theirs, &[], &settings).unwrap();
+        set_modified(&data_path(), SystemTime::now() + Duration::from_secs(10));
+        let mut ours = base.clone();
+        ours[0].items.push(Item::new("bread".into()));
+
+        let merged = saver
+            .save_now(ours.clone(), Vec::new(), base, &settings)
+            .unwrap()
+            .unwrap();
+        assert_eq!(contents(&merged.sent), contents(&ours));
+        let saved = read_lists().unwrap();
+        assert_eq!(contents(&saved), contents(&merged.lists));
+        let mut names = contents(&saved)[0].1.clone();
+        names.sort();
+        assert_eq!(names, ["bread", "eggs", "milk"]);
+        assert!(!saver.pending());
+    }
+
+    #[test]
+    fn hurried_saves_dont_wait_for_things_to_settle() {
+        let _data = temp_data();
+        let settings = Settings::default();
+        let mut saver = Saver::new(egui::Context::default());
+
+        // A change that's waiting to settle is saved along with the hurried one.
+        saver.save(
+            vec![list("Groceries", &["milk"])],
+            Vec::new(),
+            Vec::new(),
+            &settings,
+        );
+        let lists = vec![list("Groceries", &["milk", "eggs"])];
+        saver.save_soon(lists.clone(), Vec::new(), Vec::new(), &settings);
+        let started = Instant::now();
+        let result = loop {
+            if let (Some(result), _) = saver.finished() {
+                break result;
+            }
+            assert!(started.elapsed() < SAVE_DELAY, "The save waited to settle");
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert!(result.is_ok());
+        assert!(!saver.pending());
+        assert_eq!(contents(&read_lists().unwrap()), contents(&lists));
+    }
+
+    #[test]
+    fn only_backups_count_as_backups() {
+        assert!(is_backup(Path::new(
+            "/data/todoish.json.backups/20240102-030405.bak"
+        )));
+        assert!(!is_backup(Path::new("/home/me/backups/tax return.pdf")));
+        assert!(!is_backup(Path::new("20240102-030405.json")));
+        assert!(!is_backup(Path::new("20241302-030405.bak")));
+    }
+}
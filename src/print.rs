@@ -0,0 +1,62 @@
+use crate::{
+    persistence::{data_path, read_lists, read_raw},
+    Item, List,
+};
+use std::{
+    fmt::Write,
+    io::{self, Write as _},
+    process,
+};
+
+/// Write `item` (and its children) as an indented line with a check box.
+fn print_item(out: &mut String, item: &Item, depth: usize) {
+    let check = if item.is_done { '☑' } else { '☐' };
+    let important = if item.is_important { "! " } else { "" };
+    let indent = "  ".repeat(depth + 1);
+    writeln!(out, "{indent}{check} {important}{}", item.name).unwrap();
+    for child in &item.children {
+        print_item(out, child, depth + 1);
+    }
+}
+
+/// Write `lists` as plain text, with each list's name followed by its items.
+pub fn text(lists: &[List]) -> String {
+    let mut out = String::new();
+    for (idx, list) in lists.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "{}", list.name).unwrap();
+        for item in &list.items {
+            print_item(&mut out, item, 0);
+        }
+    }
+    out
+}
+
+/// Print the todo lists to stdout instead of showing the app, either as text
+/// or as the JSON from the data file. Exits with an error if the data file
+/// can't be read.
+///
+/// On Windows, the app doesn't have a console of its own, so this only shows
+/// up when the output is piped or redirected somewhere.
+pub fn run(json: bool) -> ! {
+    let out = if json {
+        read_raw().map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    } else {
+        read_lists().map(|lists| text(&lists))
+    };
+    match out {
+        Ok(out) => {
+            let mut stdout = io::stdout().lock();
+            // Nothing to be done if stdout went away, like when piped into `head`.
+            let _ = stdout.write_all(out.as_bytes());
+            let _ = stdout.flush();
+            process::exit(0)
+        }
+        Err(err) => {
+            eprintln!("Couldn't read {}: {err}", data_path().display());
+            process::exit(1)
+        }
+    }
+}
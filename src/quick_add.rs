@@ -0,0 +1,450 @@
+use crate::{
+    list_named,
+    persistence::{data_path, peek_lists, read_all, save_lists},
+    settings::Settings,
+    window_frame, Item, List,
+};
+use eframe::egui;
+use global_hotkey::{
+    hotkey::{Code, HotKey, Modifiers},
+    GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// The list that captured items go to unless told otherwise.
+pub const INBOX: &str = "Inbox";
+
+/// The parsed contents of a quick-add text box.
+pub struct QuickAdd {
+    /// The list named with a "List: item" prefix, if any.
+    pub list: Option<String>,
+    /// The name of the new item.
+    pub name: String,
+    /// Whether or not the item was prefixed with "!".
+    pub is_important: bool,
+}
+
+/// Split the "!" that marks an item as important off the front of its name,
+/// along with any more of them. Returns the name, and whether or not there
+/// was a "!".
+pub fn parse_important(text: &str) -> (&str, bool) {
+    let text = text.trim();
+    let name = text.trim_start_matches(|c: char| c == '!' || c.is_whitespace());
+    (name, name.len() != text.len())
+}
+
+/// Parse quick-add text. "Groceries: milk" targets the "Groceries" list, and a
+/// leading "!" marks the item as important. "Groceries:" on its own names the
+/// list, with no item.
+pub fn parse_quick_add(text: &str) -> QuickAdd {
+    let text = text.trim();
+    // Only split on ": " so that things like "call Bob at 10:30" stay intact.
+    let split = text
+        .split_once(": ")
+        .or_else(|| text.strip_suffix(':').map(|list| (list, "")));
+    let (list, rest) = match split {
+        Some((list, rest)) if !list.trim().is_empty() => (Some(list.trim().to_string()), rest),
+        _ => (None, text),
+    };
+    let (name, is_important) = parse_important(rest);
+
+    QuickAdd {
+        list,
+        name: name.into(),
+        is_important,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+/// An item captured by the quick-add window, along with the list it belongs in.
+pub struct Capture {
+    /// The name of the list to add the item to.
+    list: String,
+    /// The captured item.
+    item: Item,
+}
+
+impl Capture {
+    /// Add the captured item to its list, creating the list if it doesn't exist yet.
+    pub fn add_to(self, lists: &mut Vec<List>) {
+        list_named(lists, &self.list).items.push(self.item);
+    }
+}
+
+/// Register the global quick-add hotkey (Ctrl+Shift+Space). Items captured by
+/// the windows it opens are sent through `captures`. Returns `None` if global
+/// hotkeys aren't available.
+pub fn register_hotkey(
+    ctx: &egui::Context,
+    captures: mpsc::Sender<Capture>,
+) -> Option<GlobalHotKeyManager> {
+    let manager = GlobalHotKeyManager::new().ok()?;
+    manager
+        .register(HotKey::new(
+            Some(Modifiers::CONTROL | Modifiers::SHIFT),
+            Code::Space,
+        ))
+        .ok()?;
+
+    let ctx = ctx.clone();
+    GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+        if event.state() == HotKeyState::Pressed {
+            spawn_window(ctx.clone(), captures.clone());
+        }
+    }));
+    Some(manager)
+}
+
+/// Where the running app says which port it takes captures on, beside the
+/// data file, so that a quick-add window opened some other way can hand its
+/// item over instead of writing the data file out from under the app. The
+/// port is followed by a token that has to come with every capture, so that
+/// only someone who can read the file (which is only readable by its owner)
+/// can add items.
+fn port_path() -> PathBuf {
+    let mut path = data_path().into_os_string();
+    path.push(".port");
+    PathBuf::from(path)
+}
+
+/// Write the port and token to the port file, readable only by its owner.
+fn write_port_file(port: u16, token: &str) -> io::Result<()> {
+    // Start over, since an old file would keep whatever permissions it had.
+    let _ = fs::remove_file(port_path());
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    writeln!(options.open(port_path())?, "{port} {token}")
+}
+
+/// Read the port and token from the port file, if the app is running.
+fn read_port_file() -> Option<(u16, String)> {
+    let contents = fs::read_to_string(port_path()).ok()?;
+    let (port, token) = contents.trim().split_once(' ')?;
+    Some((port.parse().ok()?, token.into()))
+}
+
+/// How long to wait on the running app before giving up on it.
+const HANDOFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The most that's read from a connection, which is plenty for one item.
+const MAX_HANDOFF: u64 = 64 * 1024;
+
+/// Read a capture handed over by a quick-add window: the token on the first
+/// line, and the capture on the second. Returns None if the token is wrong.
+fn read_capture(reader: impl BufRead, token: &str) -> Option<Capture> {
+    let mut lines = reader.take(MAX_HANDOFF).lines();
+    if lines.next()?.ok()? != token {
+        return None;
+    }
+    serde_json::from_str(&lines.next()?.ok()?).ok()
+}
+
+/// Take captures from quick-add windows over a local connection, forwarding
+/// them through `captures`. If that can't be set up, quick-add windows go
+/// straight to the data file, which the app merges in.
+pub fn listen(ctx: &egui::Context, captures: mpsc::Sender<Capture>) {
+    let Ok(listener) = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) else {
+        return;
+    };
+    let Ok(addr) = listener.local_addr() else {
+        return;
+    };
+    let token = Uuid::new_v4().simple().to_string();
+    if write_port_file(addr.port(), &token).is_err() {
+        return;
+    }
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().map_while(Result::ok) {
+            // Each connection gets its own thread, so that a slow one can't
+            // hold up the rest.
+            let (ctx, captures, token) = (ctx.clone(), captures.clone(), token.clone());
+            thread::spawn(move || {
+                let _ = stream.set_read_timeout(Some(HANDOFF_TIMEOUT));
+                // Saying so lets the window know it doesn't need to save the item itself.
+                if let Some(capture) = read_capture(BufReader::new(&stream), &token) {
+                    let _ = captures.send(capture);
+                    ctx.request_repaint();
+                    let _ = stream.write_all(b"ok\n");
+                }
+            });
+        }
+    });
+}
+
+/// Stop pointing quick-add windows at this app, since it's closing.
+pub fn stop_listening() {
+    let _ = fs::remove_file(port_path());
+}
+
+/// Hand `capture` to the running app, if there is one. Returns whether or not
+/// it took it.
+fn hand_off(capture: &Capture) -> bool {
+    let Some((port, token)) = read_port_file() else {
+        return false;
+    };
+    let addr = (Ipv4Addr::LOCALHOST, port).into();
+    let Ok(mut stream) = TcpStream::connect_timeout(&addr, HANDOFF_TIMEOUT) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(HANDOFF_TIMEOUT));
+    let line = serde_json::to_string(capture).expect("Failed to serialize");
+    if write!(stream, "{token}\n{line}\n").is_err() {
+        return false;
+    }
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).is_ok() && reply.trim() == "ok"
+}
+
+/// Open a quick-add window in a new process and forward whatever it captures.
+fn spawn_window(ctx: egui::Context, captures: mpsc::Sender<Capture>) {
+    let child = env::current_exe().and_then(|exe| {
+        Command::new(exe)
+            .args(["--quick-add", "--stdout"])
+            .stdout(Stdio::piped())
+            .spawn()
+    });
+    // If the window can't be opened there's nothing to capture.
+    let Ok(mut child) = child else {
+        return;
+    };
+
+    thread::spawn(move || {
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Ok(capture) = serde_json::from_str(&line) {
+                    let _ = captures.send(capture);
+                    ctx.request_repaint();
+                }
+            }
+        }
+        let _ = child.wait();
+    });
+}
+
+/// How wide the capture window is.
+const WIDTH: f32 = 420.0;
+
+/// The tiny capture window.
+struct QuickAddWindow {
+    /// The contents of the text box.
+    text: String,
+    /// The names of every existing list, for the list picker.
+    list_names: Vec<String>,
+    /// The list that the item will be added to.
+    target: String,
+    /// Whether to hand the captured item to the running app through stdout
+    /// rather than writing it to disk ourselves.
+    to_stdout: bool,
+    /// Whether or not this is the first frame, so that focus can be stolen.
+    first_frame: bool,
+    /// Why the last item couldn't be saved, if it couldn't.
+    error: Option<String>,
+}
+
+impl QuickAddWindow {
+    fn new(to_stdout: bool) -> Self {
+        // Only peek at the lists here, since a broken file is left for the app to deal with.
+        let mut list_names: Vec<String> = peek_lists().into_iter().map(|list| list.name).collect();
+        if !list_names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(INBOX))
+        {
+            list_names.insert(0, INBOX.into());
+        }
+
+        Self {
+            text: String::new(),
+            list_names,
+            target: INBOX.into(),
+            to_stdout,
+            first_frame: true,
+            error: None,
+        }
+    }
+
+    /// Save the captured item. Returns false if there was nothing to capture,
+    /// or if it couldn't be saved.
+    fn submit(&mut self) -> bool {
+        let parsed = parse_quick_add(&self.text);
+        if parsed.name.is_empty() {
+            return false;
+        }
+
+        let mut item = Item::new(parsed.name);
+        item.set_important(parsed.is_important);
+        let capture = Capture {
+            list: parsed.list.unwrap_or_else(|| self.target.clone()),
+            item,
+        };
+
+        if self.to_stdout {
+            println!(
+                "{}",
+                serde_json::to_string(&capture).expect("Failed to serialize")
+            );
+        } else if !hand_off(&capture) {
+            // The app isn't running, so go straight to the file. A file that
+            // can't be read is left for the app to deal with.
+            let saved = read_all().and_then(|(mut lists, trash)| {
+                capture.add_to(&mut lists);
+                save_lists(&lists, &trash, &Settings::load()).map_err(|err| err.to_string())
+            });
+            if let Err(err) = saved {
+                self.error = Some(err);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl eframe::App for QuickAddWindow {
+    fn clear_color(&self, _: &egui::Visuals) -> egui::Rgba {
+        egui::Rgba::TRANSPARENT
+    }
+
+    /// The window comes and goes too quickly to be worth remembering anything about.
+    fn persist_native_window(&self) -> bool {
+        false
+    }
+
+    fn persist_egui_memory(&self) -> bool {
+        false
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        egui::CentralPanel::default()
+            .frame(window_frame(&ctx.style()))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let resp = egui::TextEdit::singleline(&mut self.text)
+                        .hint_text("new item (!important, List: item)")
+                        .desired_width(ui.available_width() - 120.0)
+                        .show(ui)
+                        .response;
+                    // Steal focus so that typing can start immediately.
+                    if self.first_frame {
+                        resp.request_focus();
+                        self.first_frame = false;
+                    }
+
+                    egui::ComboBox::from_id_source("quick-add list")
+                        .width(110.0)
+                        .selected_text(&self.target)
+                        .show_ui(ui, |ui| {
+                            for name in &self.list_names {
+                                ui.selectable_value(&mut self.target, name.clone(), name);
+                            }
+                        });
+
+                    // Escape cancels, Enter captures. Either way, we're done.
+                    let cancelled = ui.input().key_pressed(egui::Key::Escape);
+                    let submitted = resp.lost_focus()
+                        && ui.input().key_pressed(egui::Key::Enter)
+                        && self.submit();
+                    if cancelled || submitted {
+                        frame.quit();
+                    }
+                });
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::RED, format!("Couldn't save: {err}"));
+                    // Make room for the error below the text box.
+                    frame.set_window_size(egui::vec2(WIDTH, 72.0));
+                }
+            });
+    }
+}
+
+/// Show the quick-add window instead of the full app.
+pub fn run(to_stdout: bool) -> ! {
+    let native_options = eframe::NativeOptions {
+        decorated: false,
+        transparent: true,
+        always_on_top: true,
+        resizable: false,
+        initial_window_size: Some(egui::vec2(WIDTH, 48.0)),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "todoish quick add",
+        native_options,
+        Box::new(move |_| Box::new(QuickAddWindow::new(to_stdout))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_are_named_before_a_colon() {
+        let parsed = parse_quick_add("  Groceries :   milk  ");
+        assert_eq!(parsed.list.as_deref(), Some("Groceries"));
+        assert_eq!(parsed.name, "milk");
+        assert!(!parsed.is_important);
+    }
+
+    #[test]
+    fn times_are_not_list_names() {
+        let parsed = parse_quick_add("call Bob at 10:30");
+        assert_eq!(parsed.list, None);
+        assert_eq!(parsed.name, "call Bob at 10:30");
+    }
+
+    #[test]
+    fn any_number_of_bangs_mark_an_item_important() {
+        for text in ["!milk", "!!! milk", "! ! milk", "Groceries: !!milk"] {
+            let parsed = parse_quick_add(text);
+            assert_eq!(parsed.name, "milk", "{text}");
+            assert!(parsed.is_important, "{text}");
+        }
+        assert!(!parse_quick_add("milk!").is_important);
+    }
+
+    #[test]
+    fn a_list_on_its_own_has_no_item() {
+        for text in ["Groceries:", "Groceries: ", " Groceries:  !! "] {
+            let parsed = parse_quick_add(text);
+            assert_eq!(parsed.list.as_deref(), Some("Groceries"), "{text}");
+            assert!(parsed.name.is_empty(), "{text}");
+        }
+    }
+
+    #[test]
+    fn captures_need_the_token() {
+        let capture = Capture {
+            list: "Groceries".into(),
+            item: Item::new("milk".into()),
+        };
+        let line = serde_json::to_string(&capture).unwrap();
+        let read = read_capture(format!("secret\n{line}\n").as_bytes(), "secret").unwrap();
+        assert_eq!(read.list, "Groceries");
+        assert_eq!(read.item.name, "milk");
+        assert!(read_capture(format!("guess\n{line}\n").as_bytes(), "secret").is_none());
+        assert!(read_capture(format!("{line}\n").as_bytes(), "secret").is_none());
+        assert!(read_capture("secret\n".as_bytes(), "secret").is_none());
+    }
+
+    #[test]
+    fn blank_text_has_no_name() {
+        for text in ["", "   ", " ! "] {
+            let parsed = parse_quick_add(text);
+            assert!(parsed.name.is_empty(), "{text}");
+            assert_eq!(parsed.list, None, "{text}");
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{history::Event, Item, List};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+/// How often a recurring item gets unchecked again.
+pub enum Recurrence {
+    /// Every day at midnight.
+    Daily,
+    /// Every Monday.
+    Weekly,
+    /// On the first of every month.
+    Monthly,
+}
+
+impl Recurrence {
+    /// Every kind of recurrence, in the order they're offered.
+    pub const ALL: [Self; 3] = [Self::Daily, Self::Weekly, Self::Monthly];
+
+    /// A short description for menus and tooltips.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Daily => "Daily",
+            Self::Weekly => "Weekly",
+            Self::Monthly => "Monthly",
+        }
+    }
+
+    /// The first day of the period that `date` falls in.
+    fn period_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => date,
+            Self::Weekly => date - Duration::days(date.weekday().num_days_from_monday().into()),
+            Self::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// The next time something due on `date` comes due again. A monthly item
+    /// due on the 31st falls on the last day of shorter months.
+    fn advance(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Daily => date.checked_add_signed(Duration::days(1)),
+            Self::Weekly => date.checked_add_signed(Duration::days(7)),
+            Self::Monthly => date.checked_add_months(Months::new(1)),
+        }
+    }
+
+    /// Move `due` forward one step at a time until it isn't before `today`.
+    fn roll_forward(self, mut due: NaiveDate, today: NaiveDate) -> NaiveDate {
+        while due < today {
+            match self.advance(due) {
+                Some(next) => due = next,
+                None => break,
+            }
+        }
+        due
+    }
+}
+
+/// When `item` was last checked off. Items from before that was kept might
+/// still have it in their history, and otherwise the last reset is the best
+/// guess there is.
+fn completed(item: &Item) -> Option<DateTime<Local>> {
+    item.completed_at
+        .or_else(|| {
+            item.history
+                .iter()
+                .rev()
+                .find(|entry| matches!(entry.event, Event::Checked))
+                .and_then(|entry| Local.timestamp_opt(entry.time, 0).single())
+        })
+        .or(item.last_reset)
+}
+
+/// Whether or not `item` is a done, recurring item that was checked off before
+/// its current period started.
+pub fn needs_reset(item: &Item, now: DateTime<Local>) -> bool {
+    let recurrence = match (item.is_done, item.recurrence) {
+        (true, Some(recurrence)) => recurrence,
+        _ => return false,
+    };
+    match completed(item) {
+        Some(completed) => completed.date_naive() < recurrence.period_start(now.date_naive()),
+        // Nobody knows when it was done, so it was probably a while ago.
+        None => true,
+    }
+}
+
+/// Uncheck every recurring item (and sub-item) in `items` whose period has
+/// passed, moving its due date (if it has one) up to the next one that hasn't
+/// gone by yet. Returns whether or not anything was unchecked.
+pub fn sweep(items: &mut [Item], now: DateTime<Local>) -> bool {
+    let mut changed = false;
+    for item in items {
+        if needs_reset(item, now) {
+            item.set_done(false);
+            item.last_reset = Some(now);
+            if let (Some(due), Some(recurrence)) = (item.due, item.recurrence) {
+                item.set_due(Some(recurrence.roll_forward(due, now.date_naive())));
+            }
+            changed = true;
+        }
+        changed |= sweep(&mut item.children, now);
+    }
+    changed
+}
+
+/// Uncheck every item (and sub-item) in `items`.
+fn uncheck_all(items: &mut [Item]) {
+    for item in items {
+        item.set_done(false);
+        uncheck_all(&mut item.children);
+    }
+}
+
+/// Start `list` over if it resets on a schedule and its period has passed,
+/// counting days from its reset hour instead of midnight. Returns whether or
+/// not the list was changed.
+pub fn sweep_list(list: &mut List, now: DateTime<Local>) -> bool {
+    let recurrence = match list.reset {
+        Some(recurrence) => recurrence,
+        None => return false,
+    };
+    let shift = Duration::hours(list.reset_hour.into());
+    let period = recurrence.period_start((now - shift).date_naive());
+    if list
+        .last_reset
+        .is_some_and(|last| (last - shift).date_naive() >= period)
+    {
+        return false;
+    }
+    list.last_reset = Some(now);
+    uncheck_all(&mut list.items);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    /// A recurring item that was checked off at `checked`.
+    fn checked(recurrence: Recurrence, checked: DateTime<Local>) -> Item {
+        let mut item = Item::new("Water the plants".into());
+        item.recurrence = Some(recurrence);
+        item.set_done(true);
+        item.completed_at = Some(checked);
+        item
+    }
+
+    #[test]
+    fn daily_items_reset_at_midnight() {
+        let item = checked(Recurrence::Daily, at(1, 23, 59));
+        assert!(!needs_reset(&item, at(1, 23, 59)));
+        assert!(needs_reset(&item, at(2, 0, 0)));
+        let item = checked(Recurrence::Daily, at(2, 0, 0));
+        assert!(!needs_reset(&item, at(2, 23, 59)));
+    }
+
+    #[test]
+    fn weekly_items_reset_on_monday() {
+        // January 1st, 2024 was a Monday.
+        let item = checked(Recurrence::Weekly, at(1, 9, 0));
+        assert!(!needs_reset(&item, at(7, 23, 59)));
+        assert!(needs_reset(&item, at(8, 0, 0)));
+    }
+
+    #[test]
+    fn monthly_items_reset_on_the_first() {
+        let item = checked(Recurrence::Monthly, at(31, 12, 0));
+        let first = Local.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap();
+        assert!(!needs_reset(&item, at(31, 23, 59)));
+        assert!(needs_reset(&item, first));
+    }
+
+    #[test]
+    fn sweeping_moves_the_due_date_up() {
+        let mut item = checked(Recurrence::Weekly, at(1, 9, 0));
+        item.due = NaiveDate::from_ymd_opt(2024, 1, 1);
+        let mut items = vec![item];
+        assert!(sweep(&mut items, at(17, 8, 0)));
+        assert!(!items[0].is_done);
+        assert_eq!(items[0].due, NaiveDate::from_ymd_opt(2024, 1, 22));
+        assert_eq!(items[0].last_reset, Some(at(17, 8, 0)));
+        // It's been reset, so it stays that way.
+        assert!(!sweep(&mut items, at(17, 9, 0)));
+    }
+
+    #[test]
+    fn monthly_due_dates_fall_on_the_last_day_of_shorter_months() {
+        let due = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
+        assert_eq!(
+            Recurrence::Monthly.roll_forward(due, today),
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+    }
+
+    #[test]
+    fn lists_reset_at_their_reset_hour() {
+        let mut list = List::new("Morning".into());
+        list.reset = Some(Recurrence::Daily);
+        list.reset_hour = 4;
+        list.last_reset = Some(at(1, 5, 0));
+        let mut item = Item::new("Stretch".into());
+        item.set_done(true);
+        list.items.push(item);
+
+        // Until 4am, it's still the same day as far as the list is concerned.
+        assert!(!sweep_list(&mut list, at(2, 3, 59)));
+        assert!(list.items[0].is_done);
+        assert!(sweep_list(&mut list, at(2, 4, 0)));
+        assert!(!list.items[0].is_done);
+        assert!(!sweep_list(&mut list, at(3, 3, 0)));
+    }
+
+    #[test]
+    fn lists_that_were_never_reset_reset_right_away() {
+        let mut list = List::new("Morning".into());
+        list.reset = Some(Recurrence::Daily);
+        assert!(sweep_list(&mut list, at(1, 12, 0)));
+        assert!(!sweep_list(&mut list, at(1, 13, 0)));
+    }
+}
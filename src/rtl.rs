@@ -0,0 +1,40 @@
+use eframe::egui;
+
+/// Languages that are written right-to-left.
+const RTL_LANGUAGES: [&str; 8] = ["ar", "dv", "fa", "he", "iw", "ps", "ur", "yi"];
+
+/// Whether or not the system locale is written right-to-left.
+pub fn locale_is_rtl() -> bool {
+    sys_locale::get_locale().is_some_and(|locale| {
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        RTL_LANGUAGES
+            .iter()
+            .any(|rtl| language.eq_ignore_ascii_case(rtl))
+    })
+}
+
+/// A checkbox that puts the box after its text in right-to-left layouts. egui's
+/// own checkbox always draws the box on the left.
+pub fn checkbox(
+    ui: &mut egui::Ui,
+    checked: &mut bool,
+    text: impl Into<egui::WidgetText>,
+) -> egui::Response {
+    if !ui.layout().prefer_right_to_left() {
+        return ui.checkbox(checked, text);
+    }
+
+    // Horizontal layouts inside a right-aligned layout run right-to-left, so
+    // the box is added first to end up on the right.
+    ui.horizontal(|ui| {
+        let mut resp = ui.add(egui::Checkbox::new(checked, ""));
+        let label = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+        // Clicking the text toggles the box, just like a normal checkbox.
+        if label.clicked() {
+            *checked = !*checked;
+            resp.mark_changed();
+        }
+        resp.union(label)
+    })
+    .inner
+}
@@ -0,0 +1,91 @@
+use eframe::egui;
+
+/// The smallest everything can be scaled to.
+const MIN: f32 = 0.75;
+
+/// The largest everything can be scaled to.
+const MAX: f32 = 2.0;
+
+/// How much the zoom buttons change the scale by.
+pub const STEP: f32 = 0.1;
+
+/// Keep `scale` within a range where the app is still usable. Anything that
+/// isn't a number at all (like from a hand-edited settings file) goes back to 1.
+pub fn clamp(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(MIN, MAX)
+    } else {
+        1.0
+    }
+}
+
+/// Scale everything by `scale`, on top of the scale of the display itself.
+pub fn apply(ctx: &egui::Context, native_pixels_per_point: Option<f32>, scale: f32) {
+    ctx.set_pixels_per_point(native_pixels_per_point.unwrap_or(1.0) * clamp(scale));
+}
+
+/// Read the zoom shortcuts pressed this frame, returning the new scale if it
+/// changed. Ctrl+Plus and Ctrl+scroll zoom in, Ctrl+Minus and Ctrl+scroll zoom
+/// out, and Ctrl+0 goes back to normal. egui doesn't have keys for plus and
+/// minus yet, so they're read from the text typed instead. Ctrl+= counts as
+/// Ctrl+Plus, since that's the same key without Shift.
+pub fn read_keys(ctx: &egui::Context, scale: f32) -> Option<f32> {
+    let (zoom, steps, reset) = {
+        let input = ctx.input();
+        let steps = if input.modifiers.command {
+            input
+                .events
+                .iter()
+                .map(|event| match event {
+                    egui::Event::Text(text) if text == "+" || text == "=" => 1.0,
+                    egui::Event::Text(text) if text == "-" => -1.0,
+                    _ => 0.0,
+                })
+                .sum()
+        } else {
+            0.0
+        };
+        (
+            input.zoom_delta(),
+            steps,
+            input.modifiers.command && input.key_pressed(egui::Key::Num0),
+        )
+    };
+    let new = if reset {
+        1.0
+    } else {
+        clamp(scale * zoom + steps * STEP)
+    };
+    (new != scale).then_some(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `read_keys` makes of `events`, typed with Ctrl held or not.
+    fn read(events: Vec<egui::Event>, command: bool, scale: f32) -> Option<f32> {
+        let ctx = egui::Context::default();
+        ctx.begin_frame(egui::RawInput {
+            events,
+            modifiers: egui::Modifiers {
+                ctrl: command,
+                command,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        read_keys(&ctx, scale)
+    }
+
+    #[test]
+    fn ctrl_plus_and_minus_zoom() {
+        let text = |text: &str| egui::Event::Text(text.to_owned());
+
+        assert!(read(vec![text("+")], true, 1.0) == Some(1.0 + STEP));
+        assert!(read(vec![text("=")], true, 1.0) == Some(1.0 + STEP));
+        assert!(read(vec![text("-")], true, 1.0) == Some(1.0 - STEP));
+        assert!(read(vec![text("+")], true, MAX).is_none());
+        assert!(read(vec![text("+")], false, 1.0).is_none());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    trash,
+    undo::{Action, UndoStack},
+    List,
+};
+use eframe::egui;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Something to do to every selected item, applied once the lists are done being drawn.
+pub enum Bulk {
+    /// Check them all off.
+    Done,
+    /// Mark them all as important.
+    Important,
+    /// Delete them all.
+    Delete,
+    /// Move them all to the end of the list at this index.
+    MoveTo(usize),
+}
+
+impl Bulk {
+    /// Do this to every item in `lists` whose id is in `ids`. Sub-items can't
+    /// be selected, so only the top-level items are looked at. Deleted items
+    /// go in the trash.
+    pub fn apply(
+        self,
+        lists: &mut [List],
+        ids: &HashSet<Uuid>,
+        undo: &mut UndoStack,
+        trash: &mut Vec<trash::Entry>,
+    ) {
+        let mut actions = Vec::new();
+        match self {
+            Self::Done => {
+                for item in lists.iter_mut().flat_map(|list| &mut list.items) {
+                    if ids.contains(&item.id) && !item.is_done {
+                        actions.push(Action::SetDone {
+                            item: item.id,
+                            done: true,
+                        });
+                        item.set_done(true);
+                    }
+                }
+            }
+            Self::Important => {
+                for item in lists.iter_mut().flat_map(|list| &mut list.items) {
+                    if ids.contains(&item.id) {
+                        item.set_important(true);
+                    }
+                }
+            }
+            Self::Delete => {
+                for list in lists.iter_mut() {
+                    // Going backwards keeps the indices of the rest the same.
+                    for index in (0..list.items.len()).rev() {
+                        if ids.contains(&list.items[index].id) {
+                            let item = list.items.remove(index);
+                            trash.push(trash::Entry::item(&list.name, item.clone()));
+                            actions.push(Action::DeleteItem {
+                                list: list.id,
+                                parent: None,
+                                index,
+                                item: Box::new(item),
+                            });
+                        }
+                    }
+                    list.duplicate = None;
+                    list.highlight = None;
+                }
+            }
+            Self::MoveTo(target) => {
+                let to = lists[target].name.clone();
+                let mut moved = Vec::new();
+                for (l, list) in lists.iter_mut().enumerate() {
+                    if l == target {
+                        continue;
+                    }
+                    let (mut taken, kept) = std::mem::take(&mut list.items)
+                        .into_iter()
+                        .partition(|item| ids.contains(&item.id));
+                    list.items = kept;
+                    list.duplicate = None;
+                    list.highlight = None;
+                    for item in &mut taken {
+                        item.moved(&list.name, &to);
+                    }
+                    moved.append(&mut taken);
+                }
+                lists[target].items.extend(moved);
+            }
+        }
+        // The whole thing is undone in one go.
+        if !actions.is_empty() {
+            undo.push(Action::Batch(actions));
+        }
+    }
+}
+
+#[derive(Default)]
+/// The items that are selected for doing something to all at once.
+pub struct Selection {
+    /// The ids of the selected items.
+    ids: HashSet<Uuid>,
+    /// The (list, item) indices of the item that the last click started from.
+    anchor: Option<(usize, usize)>,
+    /// A range of items in one list, as (list, from, to), which is added once
+    /// the lists are done being drawn.
+    range: Option<(usize, usize, usize)>,
+}
+
+impl Selection {
+    /// Whether or not the item with this id is selected.
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// How many items are selected.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Unselect everything.
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.anchor = None;
+        self.range = None;
+    }
+
+    /// Unselect everything, returning what was selected.
+    pub fn take(&mut self) -> HashSet<Uuid> {
+        self.anchor = None;
+        self.range = None;
+        std::mem::take(&mut self.ids)
+    }
+
+    /// Handle a click on the item at (list, idx). Ctrl+click adds or removes
+    /// the item, Shift+click adds everything from the last click to it, and a
+    /// plain click on an unselected item unselects everything. Returns whether
+    /// or not the click was used for selecting, in which case it shouldn't do
+    /// anything else.
+    pub fn click(&mut self, list: usize, idx: usize, id: Uuid, modifiers: egui::Modifiers) -> bool {
+        if modifiers.shift {
+            match self.anchor {
+                Some((anchor_list, anchor)) if anchor_list == list => {
+                    self.range = Some((list, anchor.min(idx), anchor.max(idx)));
+                }
+                _ => {
+                    self.ids.insert(id);
+                    self.anchor = Some((list, idx));
+                }
+            }
+            true
+        } else if modifiers.command {
+            if !self.ids.remove(&id) {
+                self.ids.insert(id);
+            }
+            self.anchor = Some((list, idx));
+            true
+        } else {
+            if !self.ids.contains(&id) {
+                self.ids.clear();
+            }
+            self.anchor = Some((list, idx));
+            false
+        }
+    }
+
+    /// Add the range from a Shift+click, if there was one. Only the items that
+    /// are showing are added, so that a search narrows it down.
+    pub fn finish(&mut self, lists: &[List], visible: &[(usize, usize)]) {
+        if let Some((list, from, to)) = self.range.take() {
+            for &(l, idx) in visible {
+                if l == list && (from..=to).contains(&idx) {
+                    self.ids.insert(lists[l].items[idx].id);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use dirs::{config_dir, home_dir};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf, sync::OnceLock};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+/// When to use larger, touch-friendly controls.
+pub enum TouchMode {
+    /// Switch to touch mode as soon as a touch is detected.
+    #[default]
+    Auto,
+    /// Always use touch mode.
+    On,
+    /// Never use touch mode.
+    Off,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+/// Which way the layout flows.
+pub enum LayoutDirection {
+    /// Follow the system locale.
+    #[default]
+    Auto,
+    /// Always lay things out left-to-right.
+    LeftToRight,
+    /// Always lay things out right-to-left, for languages like Hebrew and Arabic.
+    RightToLeft,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+/// Whether the app is light or dark.
+pub enum Theme {
+    /// Follow the system theme, falling back to dark if it can't be detected.
+    #[default]
+    System,
+    /// Always light.
+    Light,
+    /// Always dark.
+    Dark,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// App settings. These are kept in their own file so that changing them
+/// doesn't touch the todo lists.
+pub struct Settings {
+    /// When to use larger, touch-friendly controls.
+    pub touch_mode: TouchMode,
+    /// Whether or not items can be swiped sideways to complete or delete them.
+    pub swipe_gestures: bool,
+    /// Which way the layout flows.
+    pub layout_direction: LayoutDirection,
+    /// Whether the app is light or dark.
+    pub theme: Theme,
+    /// How much bigger (or smaller) than normal everything is drawn.
+    pub ui_scale: f32,
+    /// Whether or not to point out an existing item instead of adding a duplicate.
+    pub warn_duplicates: bool,
+    /// Whether or not to send a desktop notification when an item is due.
+    pub notifications: bool,
+    /// Whether or not completed items are hidden, in lists that don't say otherwise.
+    pub hide_completed: bool,
+    /// Whether or not items that have been open for a while show how old they are.
+    pub show_age: bool,
+    /// Whether or not the first-run onboarding has been finished (or skipped).
+    pub onboarded: bool,
+    /// Whether or not the app is in its small, always on top mode.
+    pub mini_mode: bool,
+    /// The size of the window outside of mini mode, to go back to after mini
+    /// mode or the next time todoish opens.
+    pub normal_size: Option<[f32; 2]>,
+    /// Whether or not the data file is encrypted, with a key kept in the OS keyring.
+    pub encrypt: bool,
+    /// How many old copies of the data file to keep around, or 0 for none.
+    pub keep_backups: u32,
+    /// Where to save the todo lists, instead of the platform's data directory.
+    /// Only set by editing the settings file.
+    pub data_path: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            touch_mode: TouchMode::default(),
+            swipe_gestures: true,
+            layout_direction: LayoutDirection::default(),
+            theme: Theme::default(),
+            ui_scale: 1.0,
+            warn_duplicates: true,
+            notifications: true,
+            hide_completed: false,
+            show_age: true,
+            onboarded: false,
+            mini_mode: false,
+            normal_size: None,
+            encrypt: false,
+            keep_backups: 10,
+            data_path: None,
+        }
+    }
+}
+
+/// Where settings used to be saved, before moving to the platform's config directory.
+fn legacy_path() -> PathBuf {
+    let mut path = home_dir().expect("Failed to find home directory");
+    path.push(".todoish.settings");
+    path
+}
+
+/// Figure out where settings should be saved: `todoish/settings.json` in the
+/// platform's config directory, bringing over the old settings file the first
+/// time around. If they can't be put there, they stay at the old path, along
+/// with why.
+fn resolve_settings_path() -> (PathBuf, Option<String>) {
+    let mut path =
+        config_dir().unwrap_or_else(|| home_dir().expect("Failed to find home directory"));
+    path.push("todoish");
+    let legacy = legacy_path();
+    if let Err(err) = fs::create_dir_all(&path) {
+        let problem = format!(
+            "{} couldn't be created ({err}), so your settings are being kept at {} instead.",
+            path.display(),
+            legacy.display()
+        );
+        return (legacy, Some(problem));
+    }
+    path.push("settings.json");
+
+    // The old file is left where it is, like the old data file.
+    if !path.exists() && legacy.exists() {
+        if let Err(err) = fs::copy(&legacy, &path) {
+            let problem = format!(
+                "Your settings couldn't be copied to {} ({err}), so they're still being kept at {}.",
+                path.display(),
+                legacy.display()
+            );
+            return (legacy, Some(problem));
+        }
+    }
+    (path, None)
+}
+
+/// Where settings are saved, and why it's not the usual place if it isn't.
+fn resolved_settings_path() -> &'static (PathBuf, Option<String>) {
+    static PATH: OnceLock<(PathBuf, Option<String>)> = OnceLock::new();
+    PATH.get_or_init(resolve_settings_path)
+}
+
+/// The path of the file that settings are saved to.
+fn settings_path() -> PathBuf {
+    resolved_settings_path().0.clone()
+}
+
+/// Why settings are saved at the old path instead of the platform's config
+/// directory, if they had to be.
+pub fn settings_path_problem() -> Option<String> {
+    resolved_settings_path().1.clone()
+}
+
+impl Settings {
+    /// Read the settings from disk, falling back to the defaults for anything
+    /// that's missing.
+    pub fn load() -> Self {
+        fs::read(settings_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the settings to disk.
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        fs::write(settings_path(), json)
+    }
+}
@@ -0,0 +1,174 @@
+use crate::Item;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+/// A quick way to pick how long to snooze an item for.
+#[derive(Clone, Copy)]
+pub enum Preset {
+    /// A few hours from now.
+    LaterToday,
+    /// Tomorrow morning.
+    Tomorrow,
+    /// Saturday morning. On a weekend, that's next week's Saturday.
+    Weekend,
+    /// Next Monday morning.
+    NextWeek,
+}
+
+impl Preset {
+    /// Every preset, in the order they're offered.
+    pub const ALL: [Self; 4] = [
+        Self::LaterToday,
+        Self::Tomorrow,
+        Self::Weekend,
+        Self::NextWeek,
+    ];
+
+    /// The name shown in menus.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LaterToday => "Later today",
+            Self::Tomorrow => "Tomorrow",
+            Self::Weekend => "This weekend",
+            Self::NextWeek => "Next week",
+        }
+    }
+
+    /// When an item snoozed with this preset `now` wakes up.
+    pub fn until(self, now: DateTime<Local>) -> DateTime<Local> {
+        let today = now.date_naive();
+        match self {
+            Self::LaterToday => now + Duration::hours(3),
+            Self::Tomorrow => morning(today + Duration::days(1)),
+            Self::Weekend => {
+                // Saturday is day 5, counting from Monday, and today never counts.
+                let weekday = i64::from(today.weekday().num_days_from_monday());
+                let days = (4 - weekday).rem_euclid(7) + 1;
+                morning(today + Duration::days(days))
+            }
+            Self::NextWeek => {
+                let days = 7 - i64::from(today.weekday().num_days_from_monday());
+                morning(today + Duration::days(days))
+            }
+        }
+    }
+}
+
+/// The local time for `naive`. When the clocks go back, the first of the two
+/// is used, and when they skip ahead, it's pushed past the gap.
+fn local(naive: NaiveDateTime) -> DateTime<Local> {
+    (0..3)
+        .find_map(|hours| {
+            Local
+                .from_local_datetime(&(naive + Duration::hours(hours)))
+                .earliest()
+        })
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
+/// The start of the day on `date`, for snoozes that don't say when.
+fn morning(date: NaiveDate) -> DateTime<Local> {
+    local(date.and_time(NaiveTime::from_hms_opt(9, 0, 0).expect("Invalid time")))
+}
+
+/// Parse a time typed by the user. Accepts YYYY-MM-DD HH:MM, YYYY-MM-DD (in
+/// the morning), or HH:MM (the next time it comes around).
+pub fn parse(text: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let text = text.trim();
+    if let Ok(naive) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M") {
+        return Some(local(naive));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Some(morning(date));
+    }
+    let time = NaiveTime::parse_from_str(text, "%H:%M").ok()?;
+    let today = local(now.date_naive().and_time(time));
+    Some(if today > now {
+        today
+    } else {
+        local((now.date_naive() + Duration::days(1)).and_time(time))
+    })
+}
+
+/// A short label for when a snoozed item wakes up.
+pub fn label(until: DateTime<Local>, now: DateTime<Local>) -> String {
+    let time = until.format("%H:%M");
+    match (until.date_naive() - now.date_naive()).num_days() {
+        0 => format!("until {time}"),
+        1 => format!("until tomorrow {time}"),
+        2..=6 => format!("until {} {time}", until.format("%A")),
+        _ => format!("until {}", until.format("%Y-%m-%d %H:%M")),
+    }
+}
+
+/// Wake up every item in `items` whose snooze has run out. Returns whether or
+/// not anything woke up.
+///
+/// The times are compared as instants, so a change of time zone (or daylight
+/// saving) doesn't wake anything early or late. If the clock is set back, items
+/// simply stay snoozed until the clock catches up again.
+pub fn wake(items: &mut [Item], now: DateTime<Local>) -> bool {
+    let mut changed = false;
+    for item in items {
+        if item.snoozed_until.is_some_and(|until| until <= now) {
+            item.snoozed_until = None;
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A time in the week of Monday, January 1st, 2024.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn items_wake_once_their_time_comes() {
+        let mut items = vec![Item::new("Call back".into()), Item::new("Renew".into())];
+        items[0].snoozed_until = Some(at(2, 9, 0));
+        items[1].snoozed_until = Some(at(3, 9, 0));
+        assert!(!wake(&mut items, at(2, 8, 59)));
+        assert!(wake(&mut items, at(2, 9, 0)));
+        assert!(items[0].snoozed_until.is_none());
+        assert_eq!(items[1].snoozed_until, Some(at(3, 9, 0)));
+        assert!(!wake(&mut items, at(2, 9, 1)));
+    }
+
+    #[test]
+    fn labels_get_less_specific_further_out() {
+        let now = at(1, 20, 0);
+        assert_eq!(label(at(1, 23, 0), now), "until 23:00");
+        assert_eq!(label(at(2, 9, 0), now), "until tomorrow 09:00");
+        assert_eq!(label(at(7, 9, 0), now), "until Sunday 09:00");
+        assert_eq!(label(at(8, 9, 0), now), "until 2024-01-08 09:00");
+    }
+
+    #[test]
+    fn presets_land_in_the_morning() {
+        // A Wednesday evening.
+        let now = at(3, 20, 0);
+        assert_eq!(Preset::LaterToday.until(now), at(3, 23, 0));
+        assert_eq!(Preset::Tomorrow.until(now), at(4, 9, 0));
+        assert_eq!(Preset::Weekend.until(now), at(6, 9, 0));
+        assert_eq!(Preset::NextWeek.until(now), at(8, 9, 0));
+        // On a Saturday, the weekend is next week's.
+        assert_eq!(Preset::Weekend.until(at(6, 8, 0)), at(13, 9, 0));
+    }
+
+    #[test]
+    fn typed_times_are_the_next_one_to_come_around() {
+        let now = at(1, 12, 0);
+        assert_eq!(parse("2024-01-05 14:30", now), Some(at(5, 14, 30)));
+        assert_eq!(parse("2024-01-05", now), Some(at(5, 9, 0)));
+        assert_eq!(parse("18:00", now), Some(at(1, 18, 0)));
+        assert_eq!(parse("08:00", now), Some(at(2, 8, 0)));
+        assert_eq!(parse("12:00", now), Some(at(2, 12, 0)));
+        assert_eq!(parse("soon", now), None);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{Item, List};
+use chrono::{Duration, NaiveDate};
+use eframe::egui;
+
+/// How many days the chart goes back, including today.
+pub const DAYS: usize = 14;
+
+/// How tall the chart is.
+const CHART_HEIGHT: f32 = 80.0;
+
+/// Count the items (and sub-items) in `items` that were completed on each day,
+/// into `counts`, which ends with today.
+fn count(items: &[Item], today: NaiveDate, counts: &mut [usize]) {
+    for item in items {
+        // Items checked off before completion times were kept don't have one,
+        // so they aren't counted at all.
+        if let Some(completed) = item.completed_at {
+            let ago = (today - completed.date_naive()).num_days();
+            if let Some(ago) = usize::try_from(ago).ok().filter(|&ago| ago < DAYS) {
+                counts[DAYS - 1 - ago] += 1;
+            }
+        }
+        count(&item.children, today, counts);
+    }
+}
+
+/// How many items were completed on each of the last `DAYS` days (in local
+/// time), oldest first and ending with today. Archived items count too.
+pub fn completions_per_day(lists: &[List], today: NaiveDate) -> Vec<usize> {
+    let mut counts = vec![0; DAYS];
+    for list in lists {
+        count(&list.items, today, &mut counts);
+        count(&list.archived, today, &mut counts);
+    }
+    counts
+}
+
+/// Draw the stats in place of the lists: a chart of what got done over the
+/// last couple of weeks, and what's still left to do.
+pub fn show(ui: &mut egui::Ui, lists: &[List], today: NaiveDate) {
+    ui.label(egui::RichText::new(format!("Completed in the last {DAYS} days")).strong());
+    let counts = completions_per_day(lists, today);
+    let most = counts.iter().copied().max().unwrap_or(0).max(1);
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), CHART_HEIGHT),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter();
+    let slot = rect.width() / DAYS as f32;
+    let font = egui::TextStyle::Small.resolve(ui.style());
+    for (day, &n) in counts.iter().enumerate() {
+        let x = rect.left() + slot * day as f32;
+        // Leave room above the tallest bar for its number.
+        let height = (rect.height() - font.size - 2.0) * n as f32 / most as f32;
+        let bar = egui::Rect::from_x_y_ranges(
+            (x + 1.0)..=(x + slot - 1.0),
+            (rect.bottom() - height.max(1.0))..=rect.bottom(),
+        );
+        let fill = if n == 0 {
+            ui.visuals().faint_bg_color
+        } else {
+            ui.visuals().selection.bg_fill
+        };
+        painter.rect_filled(bar, 1.0, fill);
+        if n > 0 {
+            painter.text(
+                bar.center_top() - egui::vec2(0.0, 1.0),
+                egui::Align2::CENTER_BOTTOM,
+                n.to_string(),
+                font.clone(),
+                ui.visuals().weak_text_color(),
+            );
+        }
+    }
+    ui.horizontal(|ui| {
+        let first = today - Duration::days(DAYS as i64 - 1);
+        ui.label(egui::RichText::new(first.format("%b %-d").to_string()).weak());
+        ui.with_layout(egui::Layout::right_to_left(), |ui| {
+            ui.label(egui::RichText::new("today").weak());
+        });
+    });
+
+    ui.separator();
+    let open = lists
+        .iter()
+        .flat_map(|list| &list.items)
+        .filter(|item| !item.is_done);
+    let important = open.clone().filter(|item| item.is_important).count();
+    ui.label(format!("{} open, {important} important", open.count()));
+
+    // How far along each list is.
+    egui::Grid::new("list stats").num_columns(2).show(ui, |ui| {
+        for list in lists {
+            let (done, total) = list.progress();
+            ui.label(&list.name);
+            if total == 0 {
+                ui.label(egui::RichText::new("empty").weak());
+            } else {
+                ui.label(format!(
+                    "{done}/{total} ({:.0}%)",
+                    done as f32 / total as f32 * 100.0
+                ));
+            }
+            ui.end_row();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    /// An item completed `days_ago` days before January 20th, 2024.
+    fn completed(days_ago: i64) -> Item {
+        let mut item = Item::new("Done".into());
+        item.set_done(true);
+        item.completed_at =
+            Some(Local.with_ymd_and_hms(2024, 1, 20, 18, 0, 0).unwrap() - Duration::days(days_ago));
+        item
+    }
+
+    #[test]
+    fn completions_are_counted_on_the_day_they_happened() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 20).unwrap();
+        let mut parent = completed(1);
+        parent.children = vec![completed(1), completed(0)];
+        let mut list = List::new("Chores".into());
+        list.items = vec![parent, completed(0), Item::new("Not yet".into())];
+        list.archived = vec![completed(13)];
+        let mut other = List::new("Work".into());
+        // Too long ago, and somehow in the future.
+        other.items = vec![completed(14), completed(-1)];
+
+        let counts = completions_per_day(&[list, other], today);
+        assert_eq!(counts.len(), DAYS);
+        assert_eq!(counts[DAYS - 1], 2);
+        assert_eq!(counts[DAYS - 2], 2);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts.iter().sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn items_without_a_completion_time_are_left_out() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 20).unwrap();
+        let mut item = completed(0);
+        item.completed_at = None;
+        let mut list = List::new("Old".into());
+        list.items = vec![item];
+        assert!(completions_per_day(&[list], today).iter().all(|&n| n == 0));
+    }
+}
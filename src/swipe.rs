@@ -0,0 +1,145 @@
+use eframe::{egui, epaint};
+
+/// How far (in points) a row has to be swiped before letting go does anything.
+const THRESHOLD: f32 = 80.0;
+
+/// How far (in points) the pointer has to move before deciding whether it's
+/// a swipe or a scroll.
+const DEAD_ZONE: f32 = 6.0;
+
+/// A completed swipe.
+pub enum Swipe {
+    /// Swiped to the right, to complete the item.
+    Right,
+    /// Swiped to the left, to delete the item.
+    Left,
+}
+
+#[derive(Clone, Copy, Default)]
+/// The swipe state of a single row, kept in egui's temporary storage.
+struct State {
+    /// How far the row is currently shifted sideways.
+    offset: f32,
+    /// Whether the current drag is a swipe (`Some(true)`) or a scroll
+    /// (`Some(false)`). `None` until the pointer has moved far enough to tell.
+    horizontal: Option<bool>,
+}
+
+/// Draw a row that can be swiped sideways. Returns the row's response, along
+/// with the direction it was swiped in if a swipe was just completed.
+pub fn swipeable(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    enabled: bool,
+    add_row: impl FnOnce(&mut egui::Ui) -> egui::Response,
+) -> (egui::Response, Option<Swipe>) {
+    if !enabled {
+        return (add_row(ui), None);
+    }
+
+    let mut state: State = ui.data().get_temp(id).unwrap_or_default();
+
+    // Reserve a spot for the colored reveal so that it ends up behind the row.
+    let background = ui.painter().add(epaint::Shape::Noop);
+    let slot = ui.available_rect_before_wrap();
+    let row = ui
+        .allocate_ui_at_rect(slot.translate(egui::vec2(state.offset, 0.0)), add_row)
+        .inner;
+    // The whole width of the row can be swiped, not just the widget itself.
+    let rect = egui::Rect::from_min_max(
+        egui::pos2(slot.left(), row.rect.top()),
+        egui::pos2(slot.right(), row.rect.bottom()),
+    );
+
+    let drag = ui.interact(rect, id.with("drag"), egui::Sense::drag());
+    let mut swipe = None;
+    if drag.dragged() {
+        let (origin, pos, delta) = {
+            let pointer = &ui.input().pointer;
+            (
+                pointer.press_origin(),
+                pointer.interact_pos(),
+                pointer.delta(),
+            )
+        };
+        let moved = match (origin, pos) {
+            (Some(origin), Some(pos)) => pos - origin,
+            _ => egui::Vec2::ZERO,
+        };
+        // Only treat the drag as a swipe if it's mostly sideways. Everything
+        // else is handed over to the scroll area.
+        if state.horizontal.is_none() && moved.length() > DEAD_ZONE {
+            state.horizontal = Some(moved.x.abs() > moved.y.abs() * 2.0);
+        }
+        match state.horizontal {
+            Some(true) => state.offset = moved.x,
+            Some(false) => ui.scroll_with_delta(egui::vec2(0.0, delta.y)),
+            None => {}
+        }
+    } else {
+        if drag.drag_released() && state.horizontal == Some(true) {
+            if state.offset >= THRESHOLD {
+                swipe = Some(Swipe::Right);
+            } else if state.offset <= -THRESHOLD {
+                swipe = Some(Swipe::Left);
+            }
+        }
+        state.horizontal = None;
+
+        // Spring back into place.
+        let dt = ui.input().unstable_dt.min(0.1);
+        state.offset *= (1.0 - dt * 12.0).max(0.0);
+        if state.offset.abs() < 0.5 {
+            state.offset = 0.0;
+        } else {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    if state.offset != 0.0 {
+        // Swiping right completes the item, uncovering green on the left.
+        // Swiping left deletes it, uncovering red on the right. The colors are
+        // dimmed until the swipe is far enough to count.
+        let (reveal, color, icon, align) = if state.offset > 0.0 {
+            (
+                egui::Rect::from_min_max(
+                    rect.min,
+                    egui::pos2(rect.min.x + state.offset, rect.max.y),
+                ),
+                egui::Color32::from_rgb(40, 160, 70),
+                "✔",
+                egui::Align2::LEFT_CENTER,
+            )
+        } else {
+            (
+                egui::Rect::from_min_max(
+                    egui::pos2(rect.max.x + state.offset, rect.min.y),
+                    rect.max,
+                ),
+                egui::Color32::from_rgb(190, 50, 50),
+                "🗑",
+                egui::Align2::RIGHT_CENTER,
+            )
+        };
+        let alpha = if state.offset.abs() >= THRESHOLD {
+            255
+        } else {
+            120
+        };
+        let color = egui::Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha);
+        ui.painter()
+            .set(background, epaint::Shape::rect_filled(reveal, 4.0, color));
+
+        let icon_pos = align.pos_in_rect(&reveal.shrink2(egui::vec2(8.0, 0.0)));
+        ui.painter().text(
+            icon_pos,
+            align,
+            icon,
+            egui::TextStyle::Body.resolve(ui.style()),
+            egui::Color32::WHITE,
+        );
+    }
+
+    ui.data().insert_temp(id, state);
+    (row, swipe)
+}
@@ -0,0 +1,588 @@
+use crate::{Item, List};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// How often to look at the data file for changes made elsewhere.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether or not `a` and `b` would be saved exactly the same.
+pub fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Gather up every item in `items` (and their children) by id.
+fn index<'a>(items: &'a [Item], by_id: &mut HashMap<Uuid, &'a Item>) {
+    for item in items {
+        by_id.insert(item.id, item);
+        index(&item.children, by_id);
+    }
+}
+
+/// Note the time on every item in `items` that's changed since `before`, or
+/// that's new.
+fn stamp_items(items: &mut [Item], before: &HashMap<Uuid, &Item>, now: DateTime<Local>) {
+    for item in items {
+        // Children go first, so that a changed child counts as a change to its parent.
+        stamp_items(&mut item.children, before, now);
+        let changed = match before.get(&item.id) {
+            Some(old) => item.modified_at == old.modified_at && !same(item, *old),
+            None => item.modified_at.is_none(),
+        };
+        if changed {
+            item.modified_at = Some(now);
+        }
+    }
+}
+
+/// Note the time on every item in `lists` that's changed since `base`, which
+/// is what was last saved (or read).
+pub fn stamp(lists: &mut [List], base: &[List], now: DateTime<Local>) {
+    let mut before = HashMap::new();
+    for list in base {
+        index(&list.items, &mut before);
+        index(&list.archived, &mut before);
+    }
+    for list in lists {
+        stamp_items(&mut list.items, &before, now);
+        stamp_items(&mut list.archived, &before, now);
+    }
+}
+
+/// Merge one field: whichever side changed it from `base` wins, and if both
+/// did, `theirs` wins only if it's newer.
+fn pick<T: PartialEq + Clone>(base: Option<&T>, ours: &T, theirs: &T, theirs_newer: bool) -> T {
+    let ours_changed = base.is_none_or(|base| base != ours);
+    let theirs_changed = base.is_none_or(|base| base != theirs);
+    if theirs_changed && (!ours_changed || theirs_newer) {
+        theirs.clone()
+    } else {
+        ours.clone()
+    }
+}
+
+/// Whether or not both sides changed some text to something different.
+fn clash(base: Option<&String>, ours: &String, theirs: &String) -> bool {
+    ours != theirs && base.is_none_or(|base| base != ours && base != theirs)
+}
+
+/// Something that can be merged: a list, or an item in one.
+trait Merge: Clone {
+    fn id(&self) -> Uuid;
+    fn name(&self) -> &str;
+    /// Whether or not this has been changed since `base`.
+    fn changed_since(&self, base: &Self) -> bool;
+    /// Merge two copies of the same thing. If they can't be merged without
+    /// losing something, the one that lost out comes back too.
+    fn merge(base: Option<&Self>, ours: &Self, theirs: &Self) -> (Self, Option<Self>);
+}
+
+impl Merge for Item {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn changed_since(&self, base: &Self) -> bool {
+        self.modified_at != base.modified_at
+    }
+
+    fn merge(base: Option<&Self>, ours: &Self, theirs: &Self) -> (Self, Option<Self>) {
+        let theirs_newer = match base {
+            Some(base) if !ours.changed_since(base) => true,
+            Some(base) if !theirs.changed_since(base) => false,
+            _ => theirs.modified_at > ours.modified_at,
+        };
+        // Starting from our copy keeps whatever's going on with it here, like
+        // its name being edited.
+        let mut item = ours.clone();
+        item.name = pick(
+            base.map(|b| &b.name),
+            &ours.name,
+            &theirs.name,
+            theirs_newer,
+        );
+        item.is_done = pick(
+            base.map(|b| &b.is_done),
+            &ours.is_done,
+            &theirs.is_done,
+            theirs_newer,
+        );
+        item.completed_at = pick(
+            base.map(|b| &b.completed_at),
+            &ours.completed_at,
+            &theirs.completed_at,
+            theirs_newer,
+        );
+        item.is_important = pick(
+            base.map(|b| &b.is_important),
+            &ours.is_important,
+            &theirs.is_important,
+            theirs_newer,
+        );
+        item.pinned = pick(
+            base.map(|b| &b.pinned),
+            &ours.pinned,
+            &theirs.pinned,
+            theirs_newer,
+        );
+        item.priority = pick(
+            base.map(|b| &b.priority),
+            &ours.priority,
+            &theirs.priority,
+            theirs_newer,
+        );
+        item.tag = pick(base.map(|b| &b.tag), &ours.tag, &theirs.tag, theirs_newer);
+        item.tags = pick(
+            base.map(|b| &b.tags),
+            &ours.tags,
+            &theirs.tags,
+            theirs_newer,
+        );
+        item.icon = pick(
+            base.map(|b| &b.icon),
+            &ours.icon,
+            &theirs.icon,
+            theirs_newer,
+        );
+        item.context = pick(
+            base.map(|b| &b.context),
+            &ours.context,
+            &theirs.context,
+            theirs_newer,
+        );
+        item.due = pick(base.map(|b| &b.due), &ours.due, &theirs.due, theirs_newer);
+        item.effort = pick(
+            base.map(|b| &b.effort),
+            &ours.effort,
+            &theirs.effort,
+            theirs_newer,
+        );
+        item.recurrence = pick(
+            base.map(|b| &b.recurrence),
+            &ours.recurrence,
+            &theirs.recurrence,
+            theirs_newer,
+        );
+        item.last_reset = pick(
+            base.map(|b| &b.last_reset),
+            &ours.last_reset,
+            &theirs.last_reset,
+            theirs_newer,
+        );
+        item.snoozed_until = pick(
+            base.map(|b| &b.snoozed_until),
+            &ours.snoozed_until,
+            &theirs.snoozed_until,
+            theirs_newer,
+        );
+        item.notified = pick(
+            base.map(|b| &b.notified),
+            &ours.notified,
+            &theirs.notified,
+            theirs_newer,
+        );
+        item.notes = pick(
+            base.map(|b| &b.notes),
+            &ours.notes,
+            &theirs.notes,
+            theirs_newer,
+        );
+        item.links = pick(
+            base.map(|b| &b.links),
+            &ours.links,
+            &theirs.links,
+            theirs_newer,
+        );
+        item.attachments = pick(
+            base.map(|b| &b.attachments),
+            &ours.attachments,
+            &theirs.attachments,
+            theirs_newer,
+        );
+        item.blocked_by = pick(
+            base.map(|b| &b.blocked_by),
+            &ours.blocked_by,
+            &theirs.blocked_by,
+            theirs_newer,
+        );
+        if theirs_newer {
+            item.history = theirs.history.clone();
+        }
+        item.modified_at = ours.modified_at.max(theirs.modified_at);
+        item.children = merge_all(
+            base.map_or(&[], |b| &b.children),
+            &ours.children,
+            &theirs.children,
+        );
+
+        // Two different names (or notes) can't be combined, so the older one
+        // is kept as a copy beside it.
+        let conflict = clash(base.map(|b| &b.name), &ours.name, &theirs.name)
+            || clash(base.map(|b| &b.notes), &ours.notes, &theirs.notes);
+        let copy = conflict.then(|| {
+            let older = if theirs_newer { ours } else { theirs };
+            let mut copy = older.copy();
+            copy.name = format!("{} (conflict)", older.name);
+            copy
+        });
+        (item, copy)
+    }
+}
+
+impl Merge for List {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn changed_since(&self, base: &Self) -> bool {
+        let items_changed = |items: &[Item], base: &[Item]| {
+            items.iter().any(|item| {
+                base.iter()
+                    .find(|old| old.id == item.id)
+                    .is_none_or(|old| item.changed_since(old))
+            })
+        };
+        self.name != base.name
+            || self.color != base.color
+            || self.show_completed != base.show_completed
+            || self.sort_done_last != base.sort_done_last
+            || self.starred != base.starred
+            || self.folder != base.folder
+            || self.reset != base.reset
+            || self.reset_hour != base.reset_hour
+            || self.last_reset != base.last_reset
+            || items_changed(&self.items, &base.items)
+            || items_changed(&self.archived, &base.archived)
+    }
+
+    fn merge(base: Option<&Self>, ours: &Self, theirs: &Self) -> (Self, Option<Self>) {
+        // Lists don't keep track of when they were changed, so ours wins when
+        // both sides changed the same thing.
+        let mut list = ours.clone();
+        list.name = pick(base.map(|b| &b.name), &ours.name, &theirs.name, false);
+        list.color = pick(base.map(|b| &b.color), &ours.color, &theirs.color, false);
+        list.show_completed = pick(
+            base.map(|b| &b.show_completed),
+            &ours.show_completed,
+            &theirs.show_completed,
+            false,
+        );
+        list.starred = pick(
+            base.map(|b| &b.starred),
+            &ours.starred,
+            &theirs.starred,
+            false,
+        );
+        list.folder = pick(base.map(|b| &b.folder), &ours.folder, &theirs.folder, false);
+        list.sort_done_last = pick(
+            base.map(|b| &b.sort_done_last),
+            &ours.sort_done_last,
+            &theirs.sort_done_last,
+            false,
+        );
+        list.reset = pick(base.map(|b| &b.reset), &ours.reset, &theirs.reset, false);
+        list.reset_hour = pick(
+            base.map(|b| &b.reset_hour),
+            &ours.reset_hour,
+            &theirs.reset_hour,
+            false,
+        );
+        list.last_reset = ours.last_reset.max(theirs.last_reset);
+        list.items = merge_all(base.map_or(&[], |b| &b.items), &ours.items, &theirs.items);
+        list.archived = merge_all(
+            base.map_or(&[], |b| &b.archived),
+            &ours.archived,
+            &theirs.archived,
+        );
+        // The indices of the items might have changed.
+        list.duplicate = None;
+        list.highlight = None;
+        (list, None)
+    }
+}
+
+/// Whether or not the order of `side` has changed since `base`, looking only
+/// at what's in both.
+fn reordered(side: &[Uuid], base: &[Uuid]) -> bool {
+    let side_ids: HashSet<_> = side.iter().collect();
+    let base_ids: HashSet<_> = base.iter().collect();
+    !side
+        .iter()
+        .filter(|id| base_ids.contains(id))
+        .eq(base.iter().filter(|id| side_ids.contains(id)))
+}
+
+/// `primary`, with anything that's only in `secondary` slotted in after
+/// whatever comes before it there.
+fn interleave(mut primary: Vec<Uuid>, secondary: &[Uuid]) -> Vec<Uuid> {
+    for (idx, id) in secondary.iter().enumerate() {
+        if primary.contains(id) {
+            continue;
+        }
+        let at = secondary[..idx]
+            .iter()
+            .rev()
+            .find_map(|prev| primary.iter().position(|id| id == prev))
+            .map_or(0, |pos| pos + 1);
+        primary.insert(at, *id);
+    }
+    primary
+}
+
+/// Merge two versions of the same lists or items that were both changed since
+/// `base`. Things are matched up by id, or by name if both sides added them.
+/// Something one side deleted is gone, unless the other side changed it since.
+fn merge_all<T: Merge>(base: &[T], ours: &[T], theirs: &[T]) -> Vec<T> {
+    let in_base = |id: Uuid| base.iter().find(|old| old.id() == id);
+    let key = |name: &str| name.trim().to_lowercase();
+
+    // Pair up our things with theirs.
+    let mut partner: Vec<Option<usize>> = ours
+        .iter()
+        .map(|mine| theirs.iter().position(|other| other.id() == mine.id()))
+        .collect();
+    for (idx, mine) in ours.iter().enumerate() {
+        if partner[idx].is_some() || in_base(mine.id()).is_some() {
+            continue;
+        }
+        partner[idx] = theirs.iter().enumerate().position(|(other_idx, other)| {
+            !partner.contains(&Some(other_idx))
+                && in_base(other.id()).is_none()
+                && key(other.name()) == key(mine.name())
+        });
+    }
+
+    // Everything that's kept, by the id it ends up with, along with any copies
+    // left over from conflicts.
+    let mut kept: HashMap<Uuid, (T, Option<T>)> = HashMap::new();
+    let mut theirs_order = Vec::with_capacity(theirs.len());
+    for (other_idx, other) in theirs.iter().enumerate() {
+        match partner.iter().position(|&p| p == Some(other_idx)) {
+            Some(idx) => theirs_order.push(ours[idx].id()),
+            None => {
+                theirs_order.push(other.id());
+                let keep = in_base(other.id()).is_none_or(|old| other.changed_since(old));
+                if keep {
+                    kept.insert(other.id(), (other.clone(), None));
+                }
+            }
+        }
+    }
+    for (idx, mine) in ours.iter().enumerate() {
+        let old = in_base(mine.id());
+        match partner[idx] {
+            Some(other_idx) => {
+                kept.insert(mine.id(), T::merge(old, mine, &theirs[other_idx]));
+            }
+            None => {
+                if old.is_none_or(|old| mine.changed_since(old)) {
+                    kept.insert(mine.id(), (mine.clone(), None));
+                }
+            }
+        }
+    }
+
+    // Keep our order, unless only they moved things around.
+    let ours_order: Vec<Uuid> = ours.iter().map(T::id).collect();
+    let base_order: Vec<Uuid> = base.iter().map(T::id).collect();
+    let order = if reordered(&theirs_order, &base_order) && !reordered(&ours_order, &base_order) {
+        interleave(theirs_order, &ours_order)
+    } else {
+        interleave(ours_order, &theirs_order)
+    };
+    let mut merged = Vec::with_capacity(kept.len());
+    for id in order {
+        if let Some((thing, copy)) = kept.remove(&id) {
+            merged.push(thing);
+            merged.extend(copy);
+        }
+    }
+    merged
+}
+
+/// Give a new id to every item after the first with the same id, which can
+/// happen when an item was moved to another list on one side and changed on
+/// the other. Both are kept, with the later one marked as a conflict.
+fn dedupe(items: &mut [Item], seen: &mut HashSet<Uuid>) {
+    for item in items {
+        if !seen.insert(item.id) {
+            item.id = Uuid::new_v4();
+            item.name = format!("{} (conflict)", item.name);
+        }
+        dedupe(&mut item.children, seen);
+    }
+}
+
+/// Merge our lists with theirs, which were both changed since `base`. This is
+/// for when the data file was changed from somewhere else, like another
+/// computer syncing it over, so that neither side's changes are lost.
+pub fn merge(base: &[List], ours: &[List], theirs: &[List]) -> Vec<List> {
+    let mut merged = merge_all(base, ours, theirs);
+    let mut seen = HashSet::new();
+    for list in &mut merged {
+        dedupe(&mut list.items, &mut seen);
+        dedupe(&mut list.archived, &mut seen);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A list named "list" with `items` in it. It's always the same list, as
+    /// far as merging goes.
+    fn list_of(items: &[&Item]) -> List {
+        let mut list = List::new("list".into());
+        list.id = Uuid::nil();
+        list.items = items.iter().map(|&item| item.clone()).collect();
+        list
+    }
+
+    /// The names of the items in the only list in `lists`.
+    fn names(lists: &[List]) -> Vec<&str> {
+        assert_eq!(lists.len(), 1);
+        lists[0]
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect()
+    }
+
+    /// `item`, changed at `minute` past some hour.
+    fn edited(item: &Item, name: &str, minute: u32) -> Item {
+        let mut item = item.clone();
+        item.name = name.into();
+        item.modified_at = Some(Local.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap());
+        item
+    }
+
+    #[test]
+    fn interleave_slots_things_in_after_their_neighbor() {
+        let [a, b, c, x, y] = [(); 5].map(|()| Uuid::new_v4());
+        assert_eq!(interleave(vec![a, b, c], &[a, x, b]), vec![a, x, b, c]);
+        assert_eq!(interleave(vec![a, b], &[y, a]), vec![y, a, b]);
+        assert_eq!(interleave(vec![a, b], &[b, a]), vec![a, b]);
+    }
+
+    #[test]
+    fn both_sides_adding_keeps_both() {
+        let milk = Item::new("milk".into());
+        let base = [list_of(&[&milk])];
+        let mut ours = base.clone();
+        ours[0].items.push(Item::new("eggs".into()));
+        let mut theirs = base.clone();
+        theirs[0].items.push(Item::new("bread".into()));
+
+        // Theirs is slotted in after milk, where they put it.
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(names(&merged), ["milk", "bread", "eggs"]);
+    }
+
+    #[test]
+    fn both_sides_adding_the_same_name_keeps_one() {
+        let base = [list_of(&[])];
+        let mut ours = base.clone();
+        ours[0].items.push(Item::new("milk".into()));
+        let mut theirs = base.clone();
+        theirs[0].items.push(Item::new("milk".into()));
+
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(names(&merged), ["milk"]);
+    }
+
+    #[test]
+    fn deleting_loses_to_editing() {
+        let milk = Item::new("milk".into());
+        let eggs = Item::new("eggs".into());
+        let base = [list_of(&[&milk, &eggs])];
+        // We deleted both, and they changed one of them meanwhile.
+        let ours = [list_of(&[])];
+        let theirs = [list_of(&[&edited(&milk, "oat milk", 0), &eggs])];
+
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(names(&merged), ["oat milk"]);
+        // The same goes the other way around.
+        let merged = merge(&base, &theirs, &ours);
+        assert_eq!(names(&merged), ["oat milk"]);
+    }
+
+    #[test]
+    fn edits_to_different_fields_are_both_kept() {
+        let milk = Item::new("milk".into());
+        let base = [list_of(&[&milk])];
+        let ours = [list_of(&[&edited(&milk, "oat milk", 0)])];
+        let mut done = edited(&milk, "milk", 1);
+        done.is_done = true;
+        let theirs = [list_of(&[&done])];
+
+        let merged = merge(&base, &ours, &theirs);
+        assert_eq!(names(&merged), ["oat milk"]);
+        assert!(merged[0].items[0].is_done);
+    }
+
+    #[test]
+    fn clashing_renames_keep_a_copy() {
+        let milk = Item::new("milk".into());
+        let base = [list_of(&[&milk])];
+        let ours = [list_of(&[&edited(&milk, "oat milk", 0)])];
+        let theirs = [list_of(&[&edited(&milk, "soy milk", 1)])];
+
+        let merged = merge(&base, &ours, &theirs);
+        // Theirs is newer, so it wins, and ours is kept beside it.
+        assert_eq!(names(&merged), ["soy milk", "oat milk (conflict)"]);
+    }
+
+    #[test]
+    fn reordering_on_one_side_is_kept() {
+        let [a, b, c] = ["a", "b", "c"].map(|name| Item::new(name.into()));
+        let base = [list_of(&[&a, &b, &c])];
+        let mut ours = base.clone();
+        ours[0].items.push(Item::new("d".into()));
+        let theirs = [list_of(&[&c, &a, &b])];
+
+        // Our new item stays right after c, where we put it.
+        assert_eq!(names(&merge(&base, &ours, &theirs)), ["c", "d", "a", "b"]);
+        // When both sides moved things around, ours wins.
+        let ours = [list_of(&[&b, &a, &c])];
+        assert_eq!(names(&merge(&base, &ours, &theirs)), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn filing_a_list_beats_deleting_it() {
+        let base = [list_of(&[])];
+        let mut starred = base.clone();
+        starred[0].starred = true;
+        let mut filed = base.clone();
+        filed[0].folder = Some("Work".into());
+
+        for ours in [starred, filed] {
+            assert_eq!(merge(&base, &ours, &[]).len(), 1);
+            assert_eq!(merge(&base, &[], &ours).len(), 1);
+        }
+    }
+
+    #[test]
+    fn merge_all_matches_new_lists_by_name() {
+        let base: [List; 0] = [];
+        let ours = [List::new("Groceries".into())];
+        let theirs = [List::new("groceries".into()), List::new("Work".into())];
+
+        let merged = merge_all(&base, &ours, &theirs);
+        let names: Vec<&str> = merged.iter().map(|list| list.name.as_str()).collect();
+        assert_eq!(names, ["Groceries", "Work"]);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Item, List};
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+/// A colored tag for sorting items into groups, like work or home.
+pub enum Tag {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl Tag {
+    /// Every tag, in the order they're offered.
+    pub const ALL: [Self; 6] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+    ];
+
+    /// The name shown in menus.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::Orange => "Orange",
+            Self::Yellow => "Yellow",
+            Self::Green => "Green",
+            Self::Blue => "Blue",
+            Self::Purple => "Purple",
+        }
+    }
+
+    /// The color of the tag. These are bright enough to stand out on the dark
+    /// theme, but not so bright that they wash out on the light one.
+    pub fn color(self) -> Color32 {
+        match self {
+            Self::Red => Color32::from_rgb(220, 80, 80),
+            Self::Orange => Color32::from_rgb(230, 140, 50),
+            Self::Yellow => Color32::from_rgb(210, 180, 40),
+            Self::Green => Color32::from_rgb(80, 170, 90),
+            Self::Blue => Color32::from_rgb(70, 130, 220),
+            Self::Purple => Color32::from_rgb(160, 100, 210),
+        }
+    }
+}
+
+/// Tidy up a tag typed by the user, so that "Work" and "#work " are the same
+/// thing. Returns None if there's nothing left.
+fn normalize(text: &str) -> Option<String> {
+    let text = text.trim().trim_start_matches('#').trim().to_lowercase();
+    (!text.is_empty()).then(|| text.replace(' ', "-"))
+}
+
+/// Add the tags of every item (and sub-item) in `items` to `tags`.
+fn gather(items: &[Item], tags: &mut Vec<String>) {
+    for item in items {
+        for tag in &item.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        gather(&item.children, tags);
+    }
+}
+
+/// Every tag used in `lists`, sorted.
+pub fn all(lists: &[List]) -> Vec<String> {
+    let mut tags = Vec::new();
+    for list in lists {
+        gather(&list.items, &mut tags);
+    }
+    tags.sort();
+    tags
+}
+
+/// Draw a button for each tag in `in_use`, which can be clicked to only show
+/// items with that tag, or clicked again to show everything.
+pub fn filter_row(ui: &mut egui::Ui, filter: &mut Option<String>, in_use: &[String]) {
+    for tag in in_use {
+        let picked = filter.as_ref() == Some(tag);
+        let resp = ui
+            .selectable_label(picked, format!("#{tag}"))
+            .on_hover_text(if picked {
+                "Show every item".to_string()
+            } else {
+                format!("Only show items tagged {tag}")
+            });
+        if resp.clicked() {
+            *filter = (!picked).then(|| tag.clone());
+        }
+    }
+}
+
+/// Draw a checkbox for each tag already in use, for adding it to `item` or
+/// taking it off, and a box at the bottom for typing a new one. Returns
+/// whether or not the item's tags were changed.
+pub fn menu(ui: &mut egui::Ui, item: &mut Item, in_use: &[String]) -> bool {
+    let mut changed = false;
+    for tag in in_use {
+        let mut on = item.tags.contains(tag);
+        if ui.checkbox(&mut on, tag).changed() {
+            if on {
+                item.tags.push(tag.clone());
+            } else {
+                item.tags.retain(|own| own != tag);
+            }
+            changed = true;
+        }
+    }
+    // What's been typed is kept between frames while the menu is open.
+    let id = egui::Id::new(("new tag", item.id));
+    let mut text = ui.data().get_temp::<String>(id).unwrap_or_default();
+    let resp = egui::TextEdit::singleline(&mut text)
+        .hint_text("new tag")
+        .desired_width(120.0)
+        .show(ui)
+        .response;
+    if resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+        if let Some(tag) = normalize(&text) {
+            if !item.tags.contains(&tag) {
+                item.tags.push(tag);
+                changed = true;
+            }
+        }
+        text.clear();
+    }
+    ui.data().insert_temp(id, text);
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_tags_are_tidied_up() {
+        assert_eq!(normalize(" #Work "), Some("work".into()));
+        assert_eq!(normalize("Side Project"), Some("side-project".into()));
+        assert_eq!(normalize(" # "), None);
+    }
+
+    #[test]
+    fn every_tag_in_use_is_listed_once() {
+        let mut errand = Item::new("Post office".into());
+        errand.tags = vec!["home".into(), "errands".into()];
+        let mut report = Item::new("Report".into());
+        report.tags = vec!["work".into()];
+        let mut project = Item::new("Project".into());
+        project.tags = vec!["work".into()];
+        project.children.push(report);
+        let mut list = List::new("Things".into());
+        list.items = vec![errand, project];
+        assert_eq!(all(&[list]), ["errands", "home", "work"]);
+    }
+}
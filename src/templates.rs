@@ -0,0 +1,167 @@
+use crate::{
+    history::{Event, History},
+    persistence::data_path,
+    unique_list_name, Item, List,
+};
+use chrono::{DateTime, Local};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+/// The path of the file that templates are saved to, next to the todo lists.
+fn templates_path() -> PathBuf {
+    let mut path = data_path();
+    path.set_extension("templates.json");
+    path
+}
+
+/// Fill in the placeholders in `text`: `{date}`, `{weekday}`, `{month}` and
+/// `{year}`, as of `now`.
+fn expand(text: &str, now: DateTime<Local>) -> String {
+    text.replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{weekday}", &now.format("%A").to_string())
+        .replace("{month}", &now.format("%B").to_string())
+        .replace("{year}", &now.format("%Y").to_string())
+}
+
+/// Make `item` (and its children) look like it was just added: not done, not
+/// due or snoozed, not linked to anything, and with a fresh history.
+fn reset(item: &mut Item) {
+    item.is_done = false;
+    item.completed_at = None;
+    item.due = None;
+    item.modified_at = None;
+    item.last_reset = None;
+    item.snoozed_until = None;
+    item.notified = None;
+    item.links.clear();
+    item.blocked_by = None;
+    item.history = History::default();
+    item.history.push(Event::Created);
+    for child in &mut item.children {
+        reset(child);
+    }
+}
+
+/// Fill in the placeholders in the name and notes of `item` and its children.
+fn expand_all(item: &mut Item, now: DateTime<Local>) {
+    item.name = expand(&item.name, now);
+    item.notes = expand(&item.notes, now);
+    for child in &mut item.children {
+        expand_all(child, now);
+    }
+}
+
+/// Draw a button for each of `templates`, along with one for forgetting it.
+/// Returns the index of the template that was clicked, if any, and whether or
+/// not one was removed.
+fn menu<T>(
+    ui: &mut egui::Ui,
+    templates: &mut Vec<T>,
+    name: impl Fn(&T) -> &str,
+) -> (Option<usize>, bool) {
+    if templates.is_empty() {
+        ui.label(egui::RichText::new("Nothing's been saved as a template yet").weak());
+    }
+    let mut picked = None;
+    let mut remove = None;
+    for (idx, template) in templates.iter().enumerate() {
+        ui.horizontal(|ui| {
+            if ui.button(name(template)).clicked() {
+                picked = Some(idx);
+            }
+            if ui
+                .small_button("✖")
+                .on_hover_text("Remove template")
+                .clicked()
+            {
+                remove = Some(idx);
+            }
+        });
+    }
+    if let Some(idx) = remove {
+        templates.remove(idx);
+        return (None, true);
+    }
+    if picked.is_some() {
+        ui.close_menu();
+    }
+    (picked, false)
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+/// Items and lists saved for making more like them. These are kept in their
+/// own file, so that they stay put when the data file is swapped out or synced.
+pub struct Templates {
+    /// Items to copy, placeholders and all.
+    items: Vec<Item>,
+    /// Lists to copy, like a packing list or a release checklist.
+    lists: Vec<List>,
+}
+
+impl Templates {
+    /// Read the templates from disk. There are none if they can't be read.
+    pub fn load() -> Self {
+        fs::read(templates_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the templates to disk.
+    fn save(&self) {
+        let json = serde_json::to_string(self).expect("Failed to serialize");
+        fs::write(templates_path(), json).expect("Failed to write to disk");
+    }
+
+    /// Save a copy of `item` as a template.
+    pub fn add_item(&mut self, item: &Item) {
+        let mut template = item.copy();
+        reset(&mut template);
+        self.items.push(template);
+        self.save();
+    }
+
+    /// Save a copy of `list` as a template. Archived items are left out.
+    pub fn add_list(&mut self, list: &List) {
+        let mut template = list.copy();
+        template.name = list.name.clone();
+        template.archived.clear();
+        template.items.iter_mut().for_each(reset);
+        self.lists.push(template);
+        self.save();
+    }
+
+    /// Draw a button for each item template, along with one for forgetting it.
+    /// Returns a new item made from the template that was clicked, if any.
+    pub fn item_menu(&mut self, ui: &mut egui::Ui) -> Option<Item> {
+        let (picked, removed) = menu(ui, &mut self.items, |item| &item.name);
+        if removed {
+            self.save();
+        }
+        let mut item = self.items[picked?].copy();
+        reset(&mut item);
+        expand_all(&mut item, Local::now());
+        Some(item)
+    }
+
+    /// Draw a button for each list template, along with one for forgetting it.
+    /// Returns a new list made from the template that was clicked, if any,
+    /// named so that it doesn't clash with any of `lists`.
+    pub fn list_menu(&mut self, ui: &mut egui::Ui, lists: &[List]) -> Option<List> {
+        let (picked, removed) = menu(ui, &mut self.lists, |list| &list.name);
+        if removed {
+            self.save();
+        }
+        let template = &self.lists[picked?];
+        let now = Local::now();
+        let mut list = template.copy();
+        list.name = unique_list_name(lists, &expand(&template.name, now));
+        for item in &mut list.items {
+            reset(item);
+            expand_all(item, now);
+        }
+        Some(list)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{color, priority::Priority, rtl, undo::UndoStack, List};
+use chrono::NaiveDate;
+use eframe::egui::{self, collapsing_header::CollapsingState};
+
+/// The items that belong in the Today view, as (list, item) indices. That's
+/// anything not done yet (or snoozed) that's high or medium priority, or due
+/// today or earlier.
+pub fn items(lists: &[List], today: NaiveDate) -> Vec<(usize, usize)> {
+    lists
+        .iter()
+        .enumerate()
+        .flat_map(|(l, list)| {
+            list.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    !item.is_effectively_done()
+                        && item.snoozed_until.is_none()
+                        && (matches!(item.priority(), Some(Priority::High | Priority::Medium))
+                            || item.due.is_some_and(|due| due <= today))
+                })
+                .map(move |(i, _)| (l, i))
+        })
+        .collect()
+}
+
+/// Draw the Today view above the lists, gathering up the items from every list
+/// that need doing today. Checking one off checks off the real item in its own
+/// list, and the list's name next to it goes to the item there, by setting
+/// `jump`. Nothing is drawn if there's nothing to do. Returns whether or not
+/// anything was checked off.
+pub fn show(
+    ui: &mut egui::Ui,
+    lists: &mut [List],
+    undo: &mut UndoStack,
+    jump: &mut Option<(usize, usize)>,
+    today: NaiveDate,
+    rtl: bool,
+) -> bool {
+    let items = items(lists, today);
+    if items.is_empty() {
+        return false;
+    }
+
+    let mut changed = false;
+    let id = ui.make_persistent_id("today");
+    let state = CollapsingState::load_with_default_open(ui.ctx(), id, true);
+    let (_, header, _) = state
+        .show_header(ui, |ui| {
+            let name = ui.add(
+                egui::Label::new(egui::RichText::new("Today").strong()).sense(egui::Sense::click()),
+            );
+            // Show how many there are at the end of the header, like the lists' progress.
+            let end = if rtl {
+                egui::Layout::left_to_right()
+            } else {
+                egui::Layout::right_to_left()
+            };
+            ui.with_layout(end, |ui| {
+                ui.label(egui::RichText::new(items.len().to_string()).weak());
+            });
+            name
+        })
+        .body(|ui| {
+            for &(l, i) in &items {
+                // Styled like the item is in its list.
+                let mut text = egui::RichText::new(&lists[l].items[i].name);
+                match lists[l].items[i].priority() {
+                    Some(Priority::High) => text = text.underline(),
+                    Some(Priority::Medium) => text = text.italics(),
+                    _ => {}
+                }
+                // Items from colored lists take on the list's color, so it's
+                // easy to tell where they're from.
+                if let Some(list_color) = lists[l].color {
+                    text = text.color(color::tint(list_color, ui.visuals()));
+                }
+                ui.horizontal(|ui| {
+                    let home = format!("↗ {}", lists[l].name);
+                    let item = &mut lists[l].items[i];
+                    // Checking an item checks its sub-items too, like in its list.
+                    let mut done = item.is_effectively_done();
+                    if rtl::checkbox(ui, &mut done, text).changed() {
+                        undo.push(item.set_done_all(done));
+                        item.checked_at = done.then_some(ui.input().time);
+                        changed = true;
+                    }
+                    if ui
+                        .small_button(home)
+                        .on_hover_text("Show in list")
+                        .clicked()
+                    {
+                        *jump = Some((l, i));
+                        ui.ctx().request_repaint();
+                    }
+                });
+            }
+        });
+    // Clicking the name toggles the view, just like the arrow.
+    if header.inner.clicked() {
+        if let Some(mut state) = CollapsingState::load(ui.ctx(), id) {
+            state.toggle(ui);
+            state.store(ui.ctx());
+        }
+    }
+    ui.allocate_space(egui::vec2(0.0, 5.0));
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Item;
+
+    #[test]
+    fn urgent_and_due_items_are_gathered() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let mut list = List::new("Groceries".into());
+        let mut item = |name: &str, edit: fn(&mut Item)| {
+            let mut item = Item::new(name.into());
+            edit(&mut item);
+            list.items.push(item);
+        };
+        item("Milk", |_| ());
+        item("Eggs", |item| item.set_priority(Some(Priority::High)));
+        item("Bread", |item| item.set_priority(Some(Priority::Medium)));
+        item("Jam", |item| item.set_priority(Some(Priority::Low)));
+        item("Tea", |item| {
+            item.due = NaiveDate::from_ymd_opt(2024, 5, 10)
+        });
+        item("Rice", |item| {
+            item.due = NaiveDate::from_ymd_opt(2024, 5, 11)
+        });
+        // Done once all of its sub-items are.
+        item("Fruit", |item| {
+            item.set_important(true);
+            let mut apples = Item::new("Apples".into());
+            apples.set_done(true);
+            item.children.push(apples);
+        });
+        assert_eq!(items(&[list], today), [(0, 1), (0, 2), (0, 4)]);
+    }
+}
@@ -0,0 +1,86 @@
+use eframe::egui;
+
+/// How long (in seconds) a press has to be held to count as a long press. This
+/// is a bit longer than egui's maximum click duration so that letting go
+/// afterwards doesn't also count as a click.
+const LONG_PRESS_SECS: f64 = 0.65;
+
+/// How far (in points) the pointer may wander before a press stops counting as
+/// a long press. Anything further is a drag or a scroll.
+const LONG_PRESS_SLOP: f32 = 6.0;
+
+/// Enlarge (or restore) the controls to make them easier to hit with a finger.
+pub fn apply_style(ctx: &egui::Context, touch: bool) {
+    let mut style = (*ctx.style()).clone();
+    style.spacing = egui::style::Spacing::default();
+    if touch {
+        style.spacing.item_spacing.y = 8.0;
+        style.spacing.interact_size.y = 36.0;
+        style.spacing.button_padding = egui::vec2(8.0, 6.0);
+        style.spacing.icon_width = 28.0;
+        style.spacing.icon_width_inner = 16.0;
+        style.spacing.scroll_bar_width = 16.0;
+    }
+    ctx.set_style(style);
+}
+
+/// Whether or not `resp` has just been long-pressed. This only fires once per
+/// press, and is cancelled if the pointer moves too far.
+fn long_pressed(resp: &egui::Response) -> bool {
+    let id = resp.id.with("long press");
+    let ctx = &resp.ctx;
+
+    if !resp.is_pointer_button_down_on() {
+        ctx.data().remove::<bool>(id);
+        return false;
+    }
+
+    // Copy out what we need so the input lock isn't held any longer than necessary.
+    let (held_for, moved) = {
+        let input = ctx.input();
+        let pointer = &input.pointer;
+        if !pointer.primary_down() {
+            return false;
+        }
+        let held_for = pointer
+            .press_start_time()
+            .map_or(0.0, |start| input.time - start);
+        let moved = match (pointer.press_origin(), pointer.interact_pos()) {
+            (Some(origin), Some(pos)) => origin.distance(pos) > LONG_PRESS_SLOP,
+            _ => true,
+        };
+        (held_for, moved)
+    };
+
+    if moved || ctx.data().get_temp(id).unwrap_or(false) {
+        return false;
+    }
+    if held_for >= LONG_PRESS_SECS {
+        ctx.data().insert_temp(id, true);
+        return true;
+    }
+    // Keep drawing frames while the press is held so that it's noticed even
+    // if the finger doesn't move.
+    ctx.request_repaint();
+    false
+}
+
+/// Show a context menu for `resp` on right-click, or on a long press when in touch mode.
+pub fn context_menu(
+    ui: &egui::Ui,
+    resp: &egui::Response,
+    touch: bool,
+    mut add_contents: impl FnMut(&mut egui::Ui),
+) {
+    resp.clone().context_menu(|ui| add_contents(ui));
+
+    if touch {
+        // egui only opens context menus on secondary clicks, so long presses
+        // get a popup with the same contents instead.
+        let popup_id = resp.id.with("long press menu");
+        if long_pressed(resp) {
+            ui.memory().open_popup(popup_id);
+        }
+        egui::popup_below_widget(ui, popup_id, resp, |ui| add_contents(ui));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{find_list, Item, List};
+use chrono::{DateTime, Duration, Local};
+use eframe::egui::{self, collapsing_header::CollapsingState};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long deleted things are kept before they're gone for good.
+const KEEP_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+/// Something that was deleted.
+pub enum Deleted {
+    Item(Box<Item>),
+    List(List),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+/// Something in the trash, along with where it came from.
+pub struct Entry {
+    /// The name of the list it was in, or of the list itself.
+    list: String,
+    /// When it was deleted.
+    deleted_at: DateTime<Local>,
+    #[serde(flatten)]
+    /// What was deleted.
+    deleted: Deleted,
+}
+
+impl Entry {
+    /// An item that was deleted from the list named `list`.
+    pub fn item(list: &str, item: Item) -> Self {
+        Self {
+            list: list.into(),
+            deleted_at: Local::now(),
+            deleted: Deleted::Item(Box::new(item)),
+        }
+    }
+
+    /// A list that was deleted, along with everything in it.
+    pub fn list(list: List) -> Self {
+        Self {
+            list: list.name.clone(),
+            deleted_at: Local::now(),
+            deleted: Deleted::List(list),
+        }
+    }
+
+    /// The id of whatever was deleted.
+    fn id(&self) -> Uuid {
+        match &self.deleted {
+            Deleted::Item(item) => item.id,
+            Deleted::List(list) => list.id,
+        }
+    }
+
+    /// Put whatever was deleted back where it came from. If an item's list
+    /// has been deleted (or renamed) since, the list is made again.
+    pub fn restore(self, lists: &mut Vec<List>) {
+        match self.deleted {
+            Deleted::Item(item) => match find_list(lists, &self.list) {
+                Some(idx) => lists[idx].items.push(*item),
+                None => {
+                    let mut list = List::new(self.list);
+                    list.items.push(*item);
+                    lists.push(list);
+                }
+            },
+            Deleted::List(list) => lists.push(list),
+        }
+    }
+}
+
+/// Whether or not the item (or sub-item) or list with this id is in `lists`,
+/// like after deleting it was undone.
+fn present(lists: &[List], id: Uuid) -> bool {
+    fn search(items: &[Item], id: Uuid) -> bool {
+        items
+            .iter()
+            .any(|item| item.id == id || search(&item.children, id))
+    }
+    lists
+        .iter()
+        .any(|list| list.id == id || search(&list.items, id))
+}
+
+/// Throw out whatever's been in the trash for too long, along with anything
+/// that's back in the lists already.
+pub fn purge(trash: &mut Vec<Entry>, lists: &[List], now: DateTime<Local>) {
+    trash.retain(|entry| {
+        now - entry.deleted_at < Duration::days(KEEP_DAYS) && !present(lists, entry.id())
+    });
+}
+
+/// Add whatever's in `theirs` that isn't in `trash` already, like from a copy
+/// of the data file that was changed elsewhere. Returns whether or not `trash`
+/// has anything that `theirs` didn't.
+pub fn merge(trash: &mut Vec<Entry>, theirs: Vec<Entry>) -> bool {
+    let extra = trash
+        .iter()
+        .any(|entry| theirs.iter().all(|other| other.id() != entry.id()));
+    for entry in theirs {
+        if trash.iter().all(|other| other.id() != entry.id()) {
+            trash.push(entry);
+        }
+    }
+    extra
+}
+
+/// Draw the trash at the bottom of the lists. Returns whether or not anything
+/// was restored or deleted for good.
+pub fn show(ui: &mut egui::Ui, trash: &mut Vec<Entry>, lists: &mut Vec<List>) -> bool {
+    // Deleting something and then undoing it leaves it in both places, so
+    // it's only shown here while it's really gone.
+    let shown: Vec<usize> = (0..trash.len())
+        .filter(|&idx| !present(lists, trash[idx].id()))
+        .collect();
+    if shown.is_empty() {
+        return false;
+    }
+
+    let mut restore = None;
+    let mut purge = None;
+    let mut empty = false;
+    ui.allocate_space(egui::vec2(0.0, 5.0));
+    let id = ui.make_persistent_id("trash");
+    CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, |ui| {
+            ui.label(egui::RichText::new(format!("Trash ({})", shown.len())).weak());
+        })
+        .body(|ui| {
+            // Newest first.
+            for &idx in shown.iter().rev() {
+                let entry = &trash[idx];
+                ui.horizontal(|ui| {
+                    let what = match &entry.deleted {
+                        Deleted::Item(item) => format!("{} · {}", item.name, entry.list),
+                        Deleted::List(list) => format!("{} (list)", list.name),
+                    };
+                    ui.label(what).on_hover_text(format!(
+                        "Deleted {}",
+                        entry.deleted_at.format("%Y-%m-%d %H:%M")
+                    ));
+                    if ui.small_button("Restore").clicked() {
+                        restore = Some(idx);
+                    }
+                    if ui.small_button("Delete forever").clicked() {
+                        purge = Some(idx);
+                    }
+                });
+            }
+            // Emptying the trash can't be undone, so it asks first.
+            let confirm_id = id.with("confirm empty");
+            let confirming = ui.data().get_temp(confirm_id).unwrap_or(false);
+            if confirming {
+                ui.horizontal(|ui| {
+                    ui.label("Delete everything in the trash forever?");
+                    if ui.small_button("Empty").clicked() {
+                        empty = true;
+                        ui.data().remove::<bool>(confirm_id);
+                    }
+                    if ui.small_button("Cancel").clicked() {
+                        ui.data().remove::<bool>(confirm_id);
+                    }
+                });
+            } else if ui.small_button("Empty trash").clicked() {
+                ui.data().insert_temp(confirm_id, true);
+            }
+        });
+
+    if empty {
+        trash.clear();
+        return true;
+    }
+    if let Some(idx) = restore {
+        trash.remove(idx).restore(lists);
+    }
+    if let Some(idx) = purge {
+        trash.remove(idx);
+    }
+    restore.is_some() || purge.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(lists: &[List]) -> Vec<&str> {
+        lists.iter().map(|list| list.name.as_str()).collect()
+    }
+
+    #[test]
+    fn entries_survive_being_saved() {
+        let mut list = List::new("Chores".into());
+        list.items.push(Item::new("Laundry".into()));
+        let trash = vec![
+            Entry::item("Groceries", Item::new("Milk".into())),
+            Entry::list(list),
+        ];
+        let json = serde_json::to_string(&trash).unwrap();
+        let read: Vec<Entry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].id(), trash[0].id());
+        assert_eq!(read[1].id(), trash[1].id());
+        assert_eq!(read[0].list, "Groceries");
+        match &read[1].deleted {
+            Deleted::List(list) => assert_eq!(list.items[0].name, "Laundry"),
+            Deleted::Item(_) => panic!("the list came back as an item"),
+        }
+    }
+
+    #[test]
+    fn items_go_back_to_their_list() {
+        let mut lists = vec![List::new("Chores".into()), List::new("Groceries".into())];
+        Entry::item("groceries", Item::new("Milk".into())).restore(&mut lists);
+        assert_eq!(names(&lists), ["Chores", "Groceries"]);
+        assert_eq!(lists[1].items[0].name, "Milk");
+    }
+
+    #[test]
+    fn items_from_a_deleted_list_make_it_again() {
+        let mut lists = vec![List::new("Chores".into())];
+        Entry::item("Groceries", Item::new("Milk".into())).restore(&mut lists);
+        Entry::item("Groceries", Item::new("Eggs".into())).restore(&mut lists);
+        assert_eq!(names(&lists), ["Chores", "Groceries"]);
+        assert_eq!(lists[1].items.len(), 2);
+    }
+
+    #[test]
+    fn old_and_restored_entries_are_purged() {
+        let now = Local::now();
+        let restored = Item::new("Milk".into());
+        let mut lists = vec![List::new("Groceries".into())];
+        lists[0].items.push(restored.clone());
+        let mut old = Entry::item("Groceries", Item::new("Eggs".into()));
+        old.deleted_at = now - Duration::days(KEEP_DAYS);
+        let fresh = Entry::item("Groceries", Item::new("Bread".into()));
+        let fresh_id = fresh.id();
+        let mut trash = vec![Entry::item("Groceries", restored), old, fresh];
+        purge(&mut trash, &lists, now);
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id(), fresh_id);
+    }
+
+    #[test]
+    fn restored_sub_items_are_purged() {
+        let child = Item::new("Oat milk".into());
+        let mut parent = Item::new("Milk".into());
+        parent.children.push(child.clone());
+        let mut lists = vec![List::new("Groceries".into())];
+        lists[0].items.push(parent);
+        let mut trash = vec![Entry::item("Groceries", child)];
+        purge(&mut trash, &lists, Local::now());
+        assert!(trash.is_empty());
+    }
+
+    #[test]
+    fn merging_adds_only_whats_missing() {
+        let shared = Entry::item("Groceries", Item::new("Milk".into()));
+        let theirs = Entry::item("Groceries", Item::new("Eggs".into()));
+        let mut trash = vec![shared.clone()];
+        assert!(!merge(&mut trash, vec![shared.clone(), theirs]));
+        assert_eq!(trash.len(), 2);
+        assert!(merge(&mut trash, vec![shared]));
+        assert_eq!(trash.len(), 2);
+    }
+}
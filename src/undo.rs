@@ -0,0 +1,299 @@
+use crate::{Item, List};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// How many actions can be undone.
+const MAX_ACTIONS: usize = 50;
+
+/// Something the user did that can be undone.
+pub enum Action {
+    /// An item was removed from the list with the id `list`, at `index`. For
+    /// a sub-item, `parent` is the id of the item it was under.
+    DeleteItem {
+        list: Uuid,
+        parent: Option<Uuid>,
+        index: usize,
+        item: Box<Item>,
+    },
+    /// A list was removed from index `index`.
+    DeleteList { index: usize, list: List },
+    /// An item was renamed.
+    Rename {
+        item: Uuid,
+        from: String,
+        to: String,
+    },
+    /// An item was checked or unchecked.
+    SetDone { item: Uuid, done: bool },
+    /// Several things that were done at once, in order.
+    Batch(Vec<Action>),
+}
+
+/// Find an item (or sub-item) by its id.
+fn find_item(lists: &mut [List], id: Uuid) -> Option<&mut Item> {
+    fn search(items: &mut [Item], id: Uuid) -> Option<&mut Item> {
+        for item in items {
+            if item.id == id {
+                return Some(item);
+            }
+            if let Some(child) = search(&mut item.children, id) {
+                return Some(child);
+            }
+        }
+        None
+    }
+    lists
+        .iter_mut()
+        .find_map(|list| search(&mut list.items, id))
+}
+
+impl Action {
+    /// Put things back the way they were before this action. Anything that's
+    /// back already, like from the trash, is left alone.
+    fn revert(&self, lists: &mut Vec<List>) {
+        match self {
+            Self::DeleteItem {
+                list,
+                parent,
+                index,
+                item,
+            } => {
+                if find_item(lists, item.id).is_some() {
+                    return;
+                }
+                // Sub-items go back under their parent, or into the list if
+                // the parent's gone too.
+                if let Some(parent) = parent.and_then(|parent| find_item(lists, parent)) {
+                    let index = (*index).min(parent.children.len());
+                    parent.children.insert(index, (**item).clone());
+                } else if let Some(list) = lists.iter_mut().find(|other| other.id == *list) {
+                    list.items
+                        .insert((*index).min(list.items.len()), (**item).clone());
+                }
+            }
+            Self::DeleteList { index, list } => {
+                if lists.iter().all(|other| other.id != list.id) {
+                    lists.insert((*index).min(lists.len()), list.clone());
+                }
+            }
+            Self::Rename { item, from, .. } => {
+                if let Some(item) = find_item(lists, *item) {
+                    item.rename(from.clone());
+                }
+            }
+            Self::SetDone { item, done } => {
+                if let Some(item) = find_item(lists, *item) {
+                    item.set_done(!*done);
+                }
+            }
+            Self::Batch(actions) => {
+                for action in actions.iter().rev() {
+                    action.revert(lists);
+                }
+            }
+        }
+    }
+
+    /// Do this action again after it's been reverted.
+    fn apply(&self, lists: &mut Vec<List>) {
+        match self {
+            Self::DeleteItem {
+                list, parent, item, ..
+            } => {
+                if let Some(parent) = parent.and_then(|parent| find_item(lists, parent)) {
+                    parent.children.retain(|other| other.id != item.id);
+                }
+                if let Some(list) = lists.iter_mut().find(|other| other.id == *list) {
+                    list.items.retain(|other| other.id != item.id);
+                }
+            }
+            Self::DeleteList { list, .. } => {
+                lists.retain(|other| other.id != list.id);
+            }
+            Self::Rename { item, to, .. } => {
+                if let Some(item) = find_item(lists, *item) {
+                    item.rename(to.clone());
+                }
+            }
+            Self::SetDone { item, done } => {
+                if let Some(item) = find_item(lists, *item) {
+                    item.set_done(*done);
+                }
+            }
+            Self::Batch(actions) => {
+                for action in actions {
+                    action.apply(lists);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+/// The actions that can be undone, and those that can be redone.
+pub struct UndoStack {
+    /// Actions that can be undone, oldest first.
+    undo: VecDeque<Action>,
+    /// Actions that were undone and can be redone, most recently undone last.
+    redo: Vec<Action>,
+}
+
+impl UndoStack {
+    /// Remember an action that was just done. Anything that was undone can no
+    /// longer be redone after this.
+    pub fn push(&mut self, action: Action) {
+        if self.undo.len() >= MAX_ACTIONS {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(action);
+        self.redo.clear();
+    }
+
+    /// Whether or not the most recent action was deleting the item with this id,
+    /// so that undoing it would bring that item back.
+    pub fn last_deleted(&self, id: Uuid) -> bool {
+        matches!(self.undo.back(), Some(Action::DeleteItem { item, .. }) if item.id == id)
+    }
+
+    /// Undo the most recent action. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, lists: &mut Vec<List>) -> bool {
+        match self.undo.pop_back() {
+            Some(action) => {
+                action.revert(lists);
+                self.redo.push(action);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the most recently undone action. Returns false if there was nothing to redo.
+    pub fn redo(&mut self, lists: &mut Vec<List>) -> bool {
+        match self.redo.pop() {
+            Some(action) => {
+                action.apply(lists);
+                self.undo.push_back(action);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleted_items_go_back_to_their_list_after_lists_move() {
+        let mut groceries = List::new("Groceries".into());
+        let milk = Item::new("milk".into());
+        groceries.items.push(Item::new("eggs".into()));
+        let mut lists = vec![groceries, List::new("Work".into())];
+
+        let mut undo = UndoStack::default();
+        undo.push(Action::DeleteItem {
+            list: lists[0].id,
+            parent: None,
+            index: 0,
+            item: Box::new(milk),
+        });
+        lists.swap(0, 1);
+
+        assert!(undo.undo(&mut lists));
+        let names: Vec<&str> = lists[1]
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, ["milk", "eggs"]);
+        assert!(lists[0].items.is_empty());
+
+        // Redoing takes out the same item, even if it's moved.
+        lists[1].items.swap(0, 1);
+        assert!(undo.redo(&mut lists));
+        let names: Vec<&str> = lists[1]
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, ["eggs"]);
+    }
+
+    #[test]
+    fn undoing_a_deletion_leaves_restored_things_alone() {
+        let mut groceries = List::new("Groceries".into());
+        let milk = Item::new("milk".into());
+        groceries.items.push(milk.clone());
+        let chores = List::new("Chores".into());
+        let mut lists = vec![groceries, chores.clone()];
+
+        let mut undo = UndoStack::default();
+        undo.push(Action::DeleteItem {
+            list: lists[0].id,
+            parent: None,
+            index: 0,
+            item: Box::new(milk),
+        });
+        undo.push(Action::DeleteList {
+            index: 1,
+            list: chores,
+        });
+        // Both are still there, as if they'd been restored from the trash.
+        assert!(undo.undo(&mut lists));
+        assert!(undo.undo(&mut lists));
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[0].items.len(), 1);
+    }
+
+    #[test]
+    fn deleted_sub_items_go_back_under_their_parent() {
+        let mut milk = Item::new("milk".into());
+        let oat = Item::new("oat".into());
+        milk.children.push(Item::new("whole".into()));
+        let milk_id = milk.id;
+        let mut groceries = List::new("Groceries".into());
+        groceries.items.push(milk);
+        let mut lists = vec![groceries];
+
+        let mut undo = UndoStack::default();
+        undo.push(Action::DeleteItem {
+            list: lists[0].id,
+            parent: Some(milk_id),
+            index: 0,
+            item: Box::new(oat),
+        });
+        assert!(undo.undo(&mut lists));
+        let names: Vec<&str> = lists[0].items[0]
+            .children
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, ["oat", "whole"]);
+        assert_eq!(lists[0].items.len(), 1);
+
+        assert!(undo.redo(&mut lists));
+        assert_eq!(lists[0].items[0].children.len(), 1);
+    }
+
+    #[test]
+    fn only_the_latest_deletion_can_be_offered_back() {
+        let list = List::new("Groceries".into());
+        let milk = Item::new("milk".into());
+        let milk_id = milk.id;
+        let mut undo = UndoStack::default();
+        assert!(!undo.last_deleted(milk_id));
+        undo.push(Action::DeleteItem {
+            list: list.id,
+            parent: None,
+            index: 0,
+            item: Box::new(milk),
+        });
+        assert!(undo.last_deleted(milk_id));
+        undo.push(Action::SetDone {
+            item: Uuid::new_v4(),
+            done: true,
+        });
+        assert!(!undo.last_deleted(milk_id));
+    }
+}